@@ -1,182 +1,363 @@
+use crate::config_validation::{ensure_valid, validate_document};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::journal;
+use crate::mcp_clients::{
+    change_client_config, home_dir, read_client_config, ConfigPreview, McpClient,
+};
+use crate::server_entry::McpServerEntry;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{Map, Value as JsonValue};
+use sha2::{Digest, Sha256};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
-use serde_json::Value as JsonValue;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use tauri::{AppHandle, Runtime};
 
-#[derive(Debug, Serialize, Deserialize)]
+// 串行化所有对配置文件的修改
+static CONFIG_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// 用户在设置中指定的配置文件路径，启动时从 store 读取
+static CONFIG_PATH_SETTING: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+// 指定配置文件路径的环境变量，用于测试或便携安装，优先于用户设置
+pub const CONFIG_PATH_ENV: &str = "MCP_MANAGER_CLAUDE_CONFIG";
+
+const CONFIG_FILE_NAME: &str = "claude_desktop_config.json";
+
+const REVISION_KEY: &str = "__revision";
+const MCP_SERVERS_KEY: &str = "mcpServers";
+const GLOBAL_SHORTCUT_KEY: &str = "globalShortcut";
+
+// 新安装的 Claude Desktop 可能还没有配置文件，或者只有部分字段，缺少的字段按默认值处理
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ClaudeConfig {
-    #[serde(rename = "mcpServers")]
-    pub mcp_servers: HashMap<String, HashMap<String, JsonValue>>,
-    #[serde(rename = "globalShortcut")]
-    pub global_shortcut: String,
+    #[serde(rename = "mcpServers", default)]
+    pub mcp_servers: IndexMap<String, McpServerEntry>,
+    #[serde(
+        rename = "globalShortcut",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub global_shortcut: Option<String>,
+    // Claude Desktop 或其他工具写入的其它字段，原样保留
+    #[serde(flatten)]
+    pub extra: Map<String, JsonValue>,
+    // 读取时文件内容的哈希，保存时用于检测外部修改，不会写入配置文件
+    #[serde(rename = "__revision", default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
 }
 
-#[tauri::command]
-pub fn get_claude_config() -> Result<ClaudeConfig, String> {
-    let config_path = if cfg!(target_os = "macos") {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else if cfg!(target_os = "windows") {
-        let app_data =
-            env::var("APPDATA").map_err(|_| "Could not find APPDATA directory".to_string())?;
-        PathBuf::from(app_data)
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join(".config")
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    };
+// 按照磁盘上已有文件的键顺序合并新配置，新增的键追加在末尾
+pub fn merge_key_order(existing: &JsonValue, updated: JsonValue) -> JsonValue {
+    match (existing, updated) {
+        (JsonValue::Object(existing), JsonValue::Object(mut updated)) => {
+            let mut merged = Map::new();
+            for (key, old_value) in existing {
+                if let Some(value) = updated.shift_remove(key) {
+                    merged.insert(key.clone(), merge_key_order(old_value, value));
+                }
+            }
+            merged.extend(updated);
+            JsonValue::Object(merged)
+        }
+        (_, updated) => updated,
+    }
+}
 
-    let config_str = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
+pub fn content_revision(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
 
-    serde_json::from_str(&config_str).map_err(|e| format!("Failed to parse config: {}", e))
+// 配置文件当前内容的哈希，文件不存在时为 None
+pub fn current_revision(path: &Path) -> Result<Option<String>, AppError> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content_revision(&content))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AppError::io(Message::ConfigReadFailed, path, e)),
+    }
 }
 
-#[tauri::command]
-pub fn get_config_path() -> String {
+// 先写入同目录下的临时文件再重命名，保证配置文件不会被写坏
+pub fn write_file_atomic(path: &Path, content: &[u8]) -> Result<(), AppError> {
+    write_atomic(path, content, Message::ConfigWriteFailed)
+}
+
+// 与 write_file_atomic 相同，写入失败时使用 message 作为错误信息
+pub fn write_atomic(path: &Path, content: &[u8], message: Message) -> Result<(), AppError> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            AppError::internal("Invalid config path").with("path", path.display().to_string())
+        })?;
+    let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let write_result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        // 保留原文件的权限
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&tmp_path, metadata.permissions())?;
+        }
+        fs::rename(&tmp_path, path)
+    })();
+
+    if let Err(e) = write_result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(AppError::io(message, path, e));
+    }
+
+    Ok(())
+}
+
+// 错误类型由调用方决定，需要能从 AppError 转换
+pub fn with_config_lock<T, E: From<AppError>>(f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let _guard = CONFIG_LOCK
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock config: {}", e)))?;
+    f()
+}
+
+// 读取后文件被其他程序修改，前端可以提示用户重新加载
+pub fn config_conflict_error() -> AppError {
+    AppError::localized(ErrorCode::ConfigConflict, Message::ConfigConflict, &[])
+}
+
+// 配置文件路径的来源，按优先级排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigPathSource {
+    Env,
+    Setting,
+    XdgConfigHome,
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedConfigPath {
+    pub path: String,
+    pub source: ConfigPathSource,
+}
+
+// 各系统下应用配置的根目录，Linux 下优先使用 XDG_CONFIG_HOME
+pub fn user_config_dir() -> Result<(PathBuf, ConfigPathSource), AppError> {
     if cfg!(target_os = "macos") {
-        let home = env::var("HOME").unwrap_or_default();
-        format!(
-            "{}/Library/Application Support/Claude/claude_desktop_config.json",
-            home
-        )
+        let dir = home_dir()?.join("Library").join("Application Support");
+        Ok((dir, ConfigPathSource::Default))
     } else if cfg!(target_os = "windows") {
-        let app_data = env::var("APPDATA").unwrap_or_default();
-        format!("{}/Claude/claude_desktop_config.json", app_data)
+        let app_data = env::var("APPDATA").map_err(|_| AppError::home_not_found("APPDATA"))?;
+        Ok((PathBuf::from(app_data), ConfigPathSource::Default))
     } else {
-        let home = env::var("HOME").unwrap_or_default();
-        format!("{}/.config/Claude/claude_desktop_config.json", home)
+        // 规范要求 XDG_CONFIG_HOME 为绝对路径，否则忽略
+        match env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+            Some(dir) if dir.is_absolute() => Ok((dir, ConfigPathSource::XdgConfigHome)),
+            _ => Ok((home_dir()?.join(".config"), ConfigPathSource::Default)),
+        }
     }
 }
 
+// 依次检查环境变量、用户设置和系统默认位置
+pub fn resolve_config_path() -> Result<(PathBuf, ConfigPathSource), AppError> {
+    if let Some(path) = env::var_os(CONFIG_PATH_ENV).filter(|path| !path.is_empty()) {
+        return Ok((PathBuf::from(path), ConfigPathSource::Env));
+    }
+
+    let setting = CONFIG_PATH_SETTING
+        .read()
+        .map_err(|e| AppError::internal(format!("Failed to read config path setting: {}", e)))?
+        .clone();
+    if let Some(path) = setting {
+        return Ok((path, ConfigPathSource::Setting));
+    }
+
+    let (dir, source) = user_config_dir()?;
+    Ok((dir.join("Claude").join(CONFIG_FILE_NAME), source))
+}
+
+// Claude Desktop 配置文件所在路径
+pub fn config_file_path() -> Result<PathBuf, AppError> {
+    resolve_config_path().map(|(path, _)| path)
+}
+
+// 配置文件不存在时返回空配置，第一次修改时才会创建文件
 #[tauri::command]
-pub fn save_claude_config(config: ClaudeConfig) -> Result<(), String> {
-    let config_path = if cfg!(target_os = "macos") {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else if cfg!(target_os = "windows") {
-        let app_data =
-            env::var("APPDATA").map_err(|_| "Could not find APPDATA directory".to_string())?;
-        PathBuf::from(app_data)
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join(".config")
-            .join("Claude")
-            .join("claude_desktop_config.json")
+pub fn get_claude_config() -> Result<ClaudeConfig, AppError> {
+    let config_path = config_file_path()?;
+
+    let config_str = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(ClaudeConfig::default());
+        }
+        Err(e) => return Err(AppError::io(Message::ConfigReadFailed, &config_path, e)),
     };
 
-    let config_str = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    // 空文件按空配置处理
+    let document = McpClient::ClaudeDesktop.parse_document(&config_path, &config_str)?;
+    let mut config: ClaudeConfig = serde_json::from_value(JsonValue::Object(document))
+        .map_err(|e| AppError::config_parse(&config_path, e))?;
+    config.revision = Some(content_revision(config_str.as_bytes()));
 
-    fs::write(&config_path, config_str)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
+    Ok(config)
+}
 
-    Ok(())
+// 配置文件是否存在，以及缺少哪些字段
+#[derive(Debug, Serialize)]
+pub struct ClaudeConfigStatus {
+    pub path: String,
+    pub exists: bool,
+    pub missing_keys: Vec<String>,
 }
 
-// 备份配置文件
-pub fn backup_config() -> Result<(), String> {
-    let config_path = if cfg!(target_os = "macos") {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else if cfg!(target_os = "windows") {
-        let app_data =
-            env::var("APPDATA").map_err(|_| "Could not find APPDATA directory".to_string())?;
-        PathBuf::from(app_data)
-            .join("Claude")
-            .join("claude_desktop_config.json")
+#[tauri::command]
+pub fn get_claude_config_status() -> Result<ClaudeConfigStatus, AppError> {
+    let config = read_client_config(McpClient::ClaudeDesktop)?;
+    let exists = config.revision.is_some();
+    let missing_keys = if exists {
+        [MCP_SERVERS_KEY, GLOBAL_SHORTCUT_KEY]
+            .into_iter()
+            .filter(|key| !config.document.contains_key(*key))
+            .map(|key| key.to_string())
+            .collect()
     } else {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join(".config")
-            .join("Claude")
-            .join("claude_desktop_config.json")
+        vec![MCP_SERVERS_KEY.to_string(), GLOBAL_SHORTCUT_KEY.to_string()]
     };
 
-    let backup_path = config_path.with_extension("json.backup");
+    Ok(ClaudeConfigStatus {
+        path: config_file_path()?.to_string_lossy().to_string(),
+        exists,
+        missing_keys,
+    })
+}
 
-    // 如果原配置文件存在，就创建备份
-    if config_path.exists() {
-        fs::copy(&config_path, &backup_path)
-            .map_err(|e| format!("Failed to create backup: {}", e))?;
-    }
+#[tauri::command]
+pub fn get_config_path() -> String {
+    config_file_path()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
 
+// 当前使用的配置文件路径以及它的来源
+#[tauri::command]
+pub fn get_config_path_info() -> Result<ResolvedConfigPath, AppError> {
+    let (path, source) = resolve_config_path()?;
+    Ok(ResolvedConfigPath {
+        path: path.to_string_lossy().to_string(),
+        source,
+    })
+}
+
+fn set_config_path_setting(path: Option<PathBuf>) -> Result<(), AppError> {
+    *CONFIG_PATH_SETTING.write().map_err(|e| {
+        AppError::internal(format!("Failed to update config path setting: {}", e))
+    })? = path;
     Ok(())
 }
 
-// 添加这个函数来恢复备份
+// 指定配置文件路径，None 表示恢复默认位置
 #[tauri::command]
-pub fn restore_config_backup() -> Result<(), String> {
-    let config_path = if cfg!(target_os = "macos") {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join("Library")
-            .join("Application Support")
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else if cfg!(target_os = "windows") {
-        let app_data =
-            env::var("APPDATA").map_err(|_| "Could not find APPDATA directory".to_string())?;
-        PathBuf::from(app_data)
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    } else {
-        let home = env::var("HOME").map_err(|_| "Could not find home directory".to_string())?;
-        PathBuf::from(home)
-            .join(".config")
-            .join("Claude")
-            .join("claude_desktop_config.json")
-    };
+pub fn set_config_path(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<ResolvedConfigPath, AppError> {
+    let path = path.filter(|path| !path.trim().is_empty());
+    if let Some(path) = &path {
+        if !Path::new(path).is_absolute() {
+            return Err(AppError::localized(
+                ErrorCode::ConfigPathInvalid,
+                Message::ConfigPathNotAbsolute,
+                &[("path", path)],
+            )
+            .with("path", path.as_str()));
+        }
+    }
 
-    let backup_path = config_path.with_extension("json.backup");
+    crate::store::save_claude_config_path(&app, path.as_deref())?;
+    set_config_path_setting(path.map(PathBuf::from))?;
 
-    if backup_path.exists() {
-        fs::copy(&backup_path, &config_path)
-            .map_err(|e| format!("Failed to restore backup: {}", e))?;
-        fs::remove_file(&backup_path)
-            .map_err(|e| format!("Failed to remove backup file: {}", e))?;
+    // 重新监听新的配置文件并刷新托盘
+    crate::config_watcher::ensure_config_watcher(app.clone());
+    let _ = crate::tray::update_tray_status(app.clone());
+
+    get_config_path_info()
+}
+
+pub fn init_config_path<R: Runtime>(app: &AppHandle<R>) {
+    match crate::store::load_claude_config_path(app) {
+        Ok(path) => {
+            if let Err(e) = set_config_path_setting(path.map(PathBuf::from)) {
+                eprintln!("{}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to load config path setting: {}", e),
     }
+}
+
+#[tauri::command]
+pub fn save_claude_config(app: AppHandle, config: ClaudeConfig) -> Result<(), AppError> {
+    let document = serde_json::to_value(&config)
+        .map_err(|e| AppError::internal(format!("Failed to serialize config: {}", e)))?;
+    ensure_valid(&validate_document(
+        McpClient::ClaudeDesktop.servers_key(),
+        &document,
+    ))?;
 
+    let preview = change_client_config(McpClient::ClaudeDesktop, false, |client_config| {
+        // 读取之后文件被改动过则拒绝覆盖
+        if config.revision.is_some() && client_config.revision != config.revision {
+            return Err(config_conflict_error());
+        }
+        let mut document = document;
+        if let JsonValue::Object(map) = &mut document {
+            map.shift_remove(REVISION_KEY);
+        }
+        // 保持原文件中的键顺序，避免每次保存都打乱用户的配置
+        if let JsonValue::Object(map) =
+            merge_key_order(&JsonValue::Object(client_config.document.clone()), document)
+        {
+            client_config.document = map;
+        }
+        Ok(())
+    })?;
+
+    journal::record(
+        &app,
+        "Save Claude config".to_string(),
+        &[preview],
+        Default::default(),
+    );
     Ok(())
 }
 
 #[tauri::command]
-pub async fn update_global_shortcut_command(shortcut: String) -> Result<(), String> {
-    // 在修改之前创建备份
-    backup_config()?;
-
-    let mut config = get_claude_config()?;
-    
-    // 更新快捷键
-    config.global_shortcut = shortcut;
-    
-    // 保存更新后的配置
-    if let Err(e) = save_claude_config(config) {
-        // 如果保存失败，尝试恢复备份
-        restore_config_backup()?;
-        return Err(e);
-    }
+pub async fn update_global_shortcut_command(
+    app: AppHandle,
+    shortcut: String,
+    dry_run: Option<bool>,
+) -> Result<ConfigPreview, AppError> {
+    let dry_run = dry_run.unwrap_or(false);
+    let preview = change_client_config::<AppError>(McpClient::ClaudeDesktop, dry_run, |config| {
+        // 更新快捷键
+        config.document.insert(
+            GLOBAL_SHORTCUT_KEY.to_string(),
+            JsonValue::String(shortcut.clone()),
+        );
+        Ok(())
+    })?;
 
-    Ok(())
+    if !dry_run {
+        journal::record(
+            &app,
+            format!("Change global shortcut to {}", shortcut),
+            std::slice::from_ref(&preview),
+            Default::default(),
+        );
+    }
+    Ok(preview)
 }
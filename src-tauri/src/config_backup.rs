@@ -0,0 +1,381 @@
+use crate::claude_config::write_file_atomic;
+use crate::config_diff::{diff_json, ConfigChange};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::journal::JournalConfigChange;
+use crate::mcp_clients::McpClient;
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager, Runtime};
+
+pub const DEFAULT_BACKUP_RETENTION: usize = 20;
+
+// 保留的快照数量，启动时从 store 读取
+static BACKUP_RETENTION: AtomicUsize = AtomicUsize::new(DEFAULT_BACKUP_RETENTION);
+
+// app 数据目录下的 backups，启动时设置
+static BACKUP_ROOT: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+// 旧版本在客户端配置目录中创建的快照目录
+const LEGACY_BACKUP_DIR: &str = "mcp-manager-backups";
+
+#[derive(Debug, Serialize)]
+pub struct ConfigSnapshot {
+    pub id: String,
+    pub created_at: String,
+    pub size: u64,
+}
+
+fn snapshot_path(client: McpClient, id: &str) -> Result<PathBuf, AppError> {
+    // id 只允许由时间戳组成，防止路径穿越
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(AppError::localized(
+            ErrorCode::BackupIdInvalid,
+            Message::BackupIdInvalid,
+            &[("id", id)],
+        )
+        .with("id", id));
+    }
+    Ok(client.backup_dir()?.join(format!("{}.json", id)))
+}
+
+fn snapshot_ids(dir: &Path) -> Result<Vec<String>, AppError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut ids: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| AppError::io(Message::BackupReadFailed, dir, e))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+                .map(String::from)
+        })
+        .collect();
+
+    // id 按时间排序，最新的在前
+    ids.sort();
+    ids.reverse();
+    Ok(ids)
+}
+
+fn prune_snapshots(client: McpClient) -> Result<(), AppError> {
+    prune_dir(
+        &client.backup_dir()?,
+        BACKUP_RETENTION.load(Ordering::Relaxed),
+    )
+}
+
+// 只保留最新的 retention 个快照
+fn prune_dir(dir: &Path, retention: usize) -> Result<(), AppError> {
+    for id in snapshot_ids(dir)?.into_iter().skip(retention) {
+        let path = dir.join(format!("{}.json", id));
+        fs::remove_file(&path).map_err(|e| AppError::io(Message::BackupWriteFailed, &path, e))?;
+    }
+
+    Ok(())
+}
+
+fn read_json(client: McpClient, path: &Path) -> Result<JsonValue, AppError> {
+    let content =
+        fs::read_to_string(path).map_err(|e| AppError::io(Message::ConfigReadFailed, path, e))?;
+    Ok(JsonValue::Object(client.parse_document(path, &content)?))
+}
+
+// 同一毫秒内已有快照时加上序号
+fn unique_snapshot_id(dir: &Path, base_id: &str) -> String {
+    let mut id = base_id.to_string();
+    let mut counter = 1;
+    while dir.join(format!("{}.json", id)).exists() {
+        // 序号补零，保证按字符串排序与创建顺序一致
+        id = format!("{}-{:04}", base_id, counter);
+        counter += 1;
+    }
+    id
+}
+
+// 为客户端当前的配置文件创建一个带时间戳的快照，返回快照 id
+pub fn create_snapshot(client: McpClient) -> Result<Option<String>, AppError> {
+    let config_path = client.config_path()?;
+
+    // 原配置文件不存在时无需备份
+    if !config_path.exists() {
+        return Ok(None);
+    }
+
+    let dir = client.backup_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| AppError::io(Message::BackupWriteFailed, &dir, e))?;
+
+    let id = unique_snapshot_id(&dir, &Local::now().format("%Y%m%d-%H%M%S-%3f").to_string());
+    let path = dir.join(format!("{}.json", id));
+    fs::copy(&config_path, &path)
+        .map_err(|e| AppError::io(Message::BackupWriteFailed, &path, e))?;
+
+    prune_snapshots(client)?;
+
+    Ok(Some(id))
+}
+
+// 备份配置文件
+pub fn backup_config(client: McpClient) -> Result<(), AppError> {
+    create_snapshot(client).map(|_| ())
+}
+
+#[tauri::command]
+pub fn list_config_backups(client: Option<McpClient>) -> Result<Vec<ConfigSnapshot>, AppError> {
+    let dir = client.unwrap_or(McpClient::ClaudeDesktop).backup_dir()?;
+
+    snapshot_ids(&dir)?
+        .into_iter()
+        .map(|id| {
+            let path = dir.join(format!("{}.json", id));
+            let metadata = fs::metadata(&path)
+                .map_err(|e| AppError::io(Message::BackupReadFailed, &path, e))?;
+            let created_at = metadata
+                .modified()
+                .map(|time| DateTime::<Local>::from(time).to_rfc3339())
+                .unwrap_or_default();
+
+            Ok(ConfigSnapshot {
+                id,
+                created_at,
+                size: metadata.len(),
+            })
+        })
+        .collect()
+}
+
+// 返回从快照到当前配置文件的变化
+#[tauri::command]
+pub fn diff_config_backup(
+    id: String,
+    client: Option<McpClient>,
+) -> Result<Vec<ConfigChange>, AppError> {
+    let client = client.unwrap_or(McpClient::ClaudeDesktop);
+    let snapshot = read_json(client, &snapshot_path(client, &id)?)?;
+    // 配置文件已被删除时与空配置比较
+    let current = JsonValue::Object(crate::mcp_clients::read_client_config(client)?.document);
+
+    Ok(diff_json(&snapshot, &current))
+}
+
+#[tauri::command]
+pub fn restore_config_backup_by_id(
+    app: AppHandle,
+    id: String,
+    client: Option<McpClient>,
+) -> Result<(), AppError> {
+    let client = client.unwrap_or(McpClient::ClaudeDesktop);
+    let path = snapshot_path(client, &id)?;
+    if !path.exists() {
+        return Err(AppError::localized(
+            ErrorCode::BackupNotFound,
+            Message::BackupNotFound,
+            &[("id", &id)],
+        )
+        .with("id", id.as_str()));
+    }
+
+    // 确认快照内容有效后再覆盖
+    let snapshot = read_json(client, &path)?;
+    let content = fs::read(&path).map_err(|e| AppError::io(Message::BackupReadFailed, &path, e))?;
+
+    let changes = crate::claude_config::with_config_lock::<_, AppError>(|| {
+        let config_path = client.config_path()?;
+        // 当前文件损坏或不存在时按空配置处理
+        let current = read_json(client, &config_path)
+            .unwrap_or_else(|_| JsonValue::Object(Default::default()));
+
+        // 恢复前先备份当前配置，这样恢复本身也可以撤销
+        backup_config(client)?;
+        write_file_atomic(&config_path, &content)?;
+        Ok(diff_json(&current, &snapshot))
+    })?;
+
+    crate::journal::record_changes(
+        &app,
+        format!("Restore backup {}", id),
+        vec![JournalConfigChange {
+            client,
+            changes,
+            references: Vec::new(),
+        }],
+        Default::default(),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_backup_retention() -> usize {
+    BACKUP_RETENTION.load(Ordering::Relaxed)
+}
+
+#[tauri::command]
+pub fn set_backup_retention(app: AppHandle, retention: usize) -> Result<(), AppError> {
+    if retention == 0 {
+        return Err(AppError::localized(
+            ErrorCode::InvalidArguments,
+            Message::BackupRetentionInvalid,
+            &[],
+        )
+        .with("retention", retention));
+    }
+
+    crate::store::save_backup_retention(&app, retention)?;
+    BACKUP_RETENTION.store(retention, Ordering::Relaxed);
+
+    // 未设置工作区时没有工作区配置，也就没有快照
+    for client in McpClient::ALL
+        .into_iter()
+        .filter(|client| client.config_path().is_ok())
+    {
+        prune_snapshots(client)?;
+    }
+    Ok(())
+}
+
+pub fn init_backup_retention<R: Runtime>(app: &AppHandle<R>) {
+    match crate::store::load_backup_retention(app) {
+        Ok(Some(retention)) if retention > 0 => {
+            BACKUP_RETENTION.store(retention, Ordering::Relaxed);
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to load backup retention: {}", e),
+    }
+}
+
+pub fn backup_root() -> Result<PathBuf, AppError> {
+    BACKUP_ROOT
+        .read()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .ok_or_else(|| AppError::internal("Backup directory is not initialized"))
+}
+
+fn legacy_backup_dir(client: McpClient) -> Option<PathBuf> {
+    let dir = match client {
+        McpClient::ClaudeCode => crate::mcp_clients::home_dir().ok()?.join(".claude"),
+        // 工作区配置是新加的，没有旧快照
+        McpClient::VscodeWorkspace => return None,
+        _ => client.config_path().ok()?.parent()?.to_path_buf(),
+    };
+    Some(dir.join(LEGACY_BACKUP_DIR))
+}
+
+// 把旧版本留在客户端目录中的快照移到数据目录，移完后删除旧目录
+fn migrate_legacy_snapshots(client: McpClient) -> Result<(), AppError> {
+    let Some(legacy) = legacy_backup_dir(client) else {
+        return Ok(());
+    };
+    if !legacy.is_dir() {
+        return Ok(());
+    }
+
+    let dir = client.backup_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| AppError::io(Message::BackupWriteFailed, &dir, e))?;
+    for id in snapshot_ids(&legacy)? {
+        let file_name = format!("{}.json", id);
+        let target = dir.join(&file_name);
+        let source = legacy.join(&file_name);
+        if !target.exists() {
+            fs::copy(&source, &target)
+                .map_err(|e| AppError::io(Message::BackupWriteFailed, &source, e))?;
+        }
+        fs::remove_file(&source)
+            .map_err(|e| AppError::io(Message::BackupWriteFailed, &source, e))?;
+    }
+    // 目录中还有其它文件时保留
+    let _ = fs::remove_dir(&legacy);
+    Ok(())
+}
+
+pub fn init_backup_dir<R: Runtime>(app: &AppHandle<R>) {
+    let dir = match app.path().app_data_dir() {
+        Ok(dir) => dir.join("backups"),
+        Err(e) => {
+            eprintln!("Failed to get data directory: {}", e);
+            return;
+        }
+    };
+    if let Ok(mut root) = BACKUP_ROOT.write() {
+        *root = Some(dir);
+    }
+
+    for client in McpClient::ALL {
+        if let Err(e) = migrate_legacy_snapshots(client) {
+            eprintln!("Failed to move backups of {}: {}", client.display_name(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("mcp-config-backup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn touch(dir: &Path, id: &str) {
+        fs::write(dir.join(format!("{}.json", id)), "{}").unwrap();
+    }
+
+    #[test]
+    fn ids_in_the_same_millisecond_sort_in_creation_order() {
+        let dir = test_dir("ids");
+        let base = "20240101-120000-000";
+        let mut created = Vec::new();
+        for _ in 0..12 {
+            let id = unique_snapshot_id(&dir, base);
+            touch(&dir, &id);
+            created.push(id);
+        }
+        touch(&dir, "20240101-120000-001");
+
+        let mut expected: Vec<String> = created.into_iter().rev().collect();
+        expected.insert(0, "20240101-120000-001".to_string());
+        assert_eq!(snapshot_ids(&dir).unwrap(), expected);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn pruning_keeps_the_newest_snapshots() {
+        let dir = test_dir("prune");
+        for id in [
+            "20240101-120000-000",
+            "20240102-120000-000",
+            "20240102-120000-000-0001",
+            "20240103-120000-000",
+        ] {
+            touch(&dir, id);
+        }
+
+        prune_dir(&dir, 2).unwrap();
+        assert_eq!(
+            snapshot_ids(&dir).unwrap(),
+            vec!["20240103-120000-000", "20240102-120000-000-0001"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_ids_outside_the_backup_dir() {
+        for id in ["", "../config", "2024/01", "latest"] {
+            let error = snapshot_path(McpClient::ClaudeDesktop, id).unwrap_err();
+            assert_eq!(error.code, ErrorCode::BackupIdInvalid);
+        }
+    }
+}
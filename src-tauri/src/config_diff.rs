@@ -0,0 +1,243 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+// 单个字段的变化，path 为 JSON Pointer (RFC 6901)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<JsonValue>,
+}
+
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+pub fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn diff_at(path: &str, before: &JsonValue, after: &JsonValue, changes: &mut Vec<ConfigChange>) {
+    match (before, after) {
+        (JsonValue::Object(before), JsonValue::Object(after)) => {
+            for (key, old_value) in before {
+                let child = format!("{}/{}", path, escape_pointer_token(key));
+                match after.get(key) {
+                    Some(new_value) => diff_at(&child, old_value, new_value, changes),
+                    None => changes.push(ConfigChange {
+                        kind: ChangeKind::Removed,
+                        path: child,
+                        before: Some(old_value.clone()),
+                        after: None,
+                    }),
+                }
+            }
+            for (key, new_value) in after {
+                if !before.contains_key(key) {
+                    changes.push(ConfigChange {
+                        kind: ChangeKind::Added,
+                        path: format!("{}/{}", path, escape_pointer_token(key)),
+                        before: None,
+                        after: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        // 数组和基本类型整体比较
+        (before, after) if before != after => changes.push(ConfigChange {
+            kind: ChangeKind::Modified,
+            path: path.to_string(),
+            before: Some(before.clone()),
+            after: Some(after.clone()),
+        }),
+        _ => {}
+    }
+}
+
+// 比较两个 JSON 文档，返回从 before 到 after 的全部变化
+pub fn diff_json(before: &JsonValue, after: &JsonValue) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    diff_at("", before, after, &mut changes);
+    changes
+}
+
+// 把 changes 应用到 document 上，reverse 时反向应用（撤销）。
+// 每个字段的当前值必须与变化前的值一致，否则说明文件已被其它修改覆盖
+pub fn apply_changes(
+    document: &mut JsonValue,
+    changes: &[ConfigChange],
+    reverse: bool,
+) -> Result<(), AppError> {
+    let ordered: Vec<&ConfigChange> = if reverse {
+        changes.iter().rev().collect()
+    } else {
+        changes.iter().collect()
+    };
+
+    for change in ordered {
+        let (expected, target) = if reverse {
+            (&change.after, &change.before)
+        } else {
+            (&change.before, &change.after)
+        };
+
+        if document.pointer(&change.path) != expected.as_ref() {
+            let path = if change.path.is_empty() {
+                "/"
+            } else {
+                &change.path
+            };
+            return Err(AppError::localized(
+                ErrorCode::ConfigConflict,
+                Message::ConfigFieldChanged,
+                &[("path", path)],
+            )
+            .with("pointer", path));
+        }
+
+        if change.path.is_empty() {
+            if let Some(target) = target {
+                *document = target.clone();
+            }
+            continue;
+        }
+
+        // diff_json 不会进入数组，父节点一定是对象
+        let (parent, token) = change.path.rsplit_once('/').unwrap_or(("", &change.path));
+        let parent = document
+            .pointer_mut(parent)
+            .and_then(|v| v.as_object_mut())
+            .ok_or_else(|| {
+                AppError::localized(
+                    ErrorCode::ConfigConflict,
+                    Message::ConfigFieldMissing,
+                    &[("path", parent)],
+                )
+                .with("pointer", parent)
+            })?;
+        let key = unescape_pointer_token(token);
+        match target {
+            Some(value) => {
+                parent.insert(key, value.clone());
+            }
+            None => {
+                parent.shift_remove(&key);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diffs_nested_objects_by_pointer() {
+        let before = json!({
+            "mcpServers": { "a": { "command": "node" }, "b/c": { "command": "uv" } },
+            "theme": "dark"
+        });
+        let after = json!({
+            "mcpServers": { "a": { "command": "bun" }, "d": { "command": "npx" } },
+            "theme": "dark"
+        });
+
+        let changes = diff_json(&before, &after);
+        let summary: Vec<(ChangeKind, &str)> = changes
+            .iter()
+            .map(|change| (change.kind, change.path.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Modified, "/mcpServers/a/command"),
+                (ChangeKind::Removed, "/mcpServers/b~1c"),
+                (ChangeKind::Added, "/mcpServers/d"),
+            ]
+        );
+        assert_eq!(changes[1].before, Some(json!({ "command": "uv" })));
+        assert_eq!(changes[2].after, Some(json!({ "command": "npx" })));
+    }
+
+    #[test]
+    fn compares_arrays_as_a_whole() {
+        let changes = diff_json(
+            &json!({ "args": ["a", "b"] }),
+            &json!({ "args": ["a", "c"] }),
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "/args");
+        assert_eq!(changes[0].after, Some(json!(["a", "c"])));
+    }
+
+    #[test]
+    fn equal_documents_have_no_changes() {
+        let document = json!({ "mcpServers": { "a": { "args": [1, 2] } } });
+        assert!(diff_json(&document, &document).is_empty());
+    }
+
+    #[test]
+    fn applies_and_reverts_changes() {
+        let before = json!({
+            "mcpServers": { "a": { "command": "node" }, "b": { "command": "uv" } },
+            "theme": "dark"
+        });
+        let after = json!({
+            "mcpServers": { "a": { "command": "bun" }, "c": { "command": "npx" } },
+            "theme": "dark"
+        });
+        let changes = diff_json(&before, &after);
+
+        let mut document = before.clone();
+        apply_changes(&mut document, &changes, false).unwrap();
+        assert_eq!(document, after);
+
+        apply_changes(&mut document, &changes, true).unwrap();
+        assert_eq!(document, before);
+    }
+
+    #[test]
+    fn refuses_to_overwrite_other_edits() {
+        let before = json!({ "mcpServers": { "a": { "command": "node" } } });
+        let after = json!({ "mcpServers": { "a": { "command": "bun" } } });
+        let changes = diff_json(&before, &after);
+
+        // 撤销前文件已被其它程序改动
+        let mut document = json!({ "mcpServers": { "a": { "command": "deno" } } });
+        let error = apply_changes(&mut document, &changes, true).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ConfigConflict);
+        assert_eq!(error.context["pointer"], "/mcpServers/a/command");
+
+        // 重做时父节点已不存在
+        let mut document = json!({});
+        let changes = diff_json(
+            &json!({ "a": { "b": 1 } }),
+            &json!({ "a": { "b": 1, "c": 2 } }),
+        );
+        let error = apply_changes(&mut document, &changes, false).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ConfigConflict);
+        assert_eq!(error.context["pointer"], "/a");
+    }
+
+    #[test]
+    fn escapes_pointer_tokens() {
+        assert_eq!(escape_pointer_token("a/b~c"), "a~1b~0c");
+        assert_eq!(unescape_pointer_token("a~1b~0c"), "a/b~c");
+    }
+}
@@ -0,0 +1,449 @@
+use crate::config_diff::escape_pointer_token;
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::{t_args, Message};
+use crate::mcp_clients::McpClient;
+use crate::server_entry::McpServerEntry;
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    // 出问题字段的 JSON Pointer
+    pub pointer: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(pointer: String, message: Message, args: &[(&str, &str)]) -> Self {
+        Diagnostic {
+            pointer,
+            severity: Severity::Error,
+            message: t_args(message, args),
+        }
+    }
+
+    fn warning(pointer: String, message: Message, args: &[(&str, &str)]) -> Self {
+        Diagnostic {
+            pointer,
+            severity: Severity::Warning,
+            message: t_args(message, args),
+        }
+    }
+}
+
+// 在 PATH 中查找命令，命令本身是路径时直接检查文件是否存在
+pub fn resolve_command(command: &str) -> Option<PathBuf> {
+    let path = Path::new(command);
+    if path.components().count() > 1 || path.is_absolute() {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    let extensions: Vec<String> = if cfg!(target_os = "windows") {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string())
+            .split(';')
+            .map(|ext| ext.to_string())
+            .chain(std::iter::once(String::new()))
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let candidate = dir.join(format!("{}{}", command, ext));
+            candidate.is_file().then_some(candidate)
+        })
+    })
+}
+
+// 看起来像本地绝对路径的参数
+fn looks_like_path(arg: &str) -> bool {
+    arg.starts_with('/')
+        || arg.starts_with("~/")
+        || matches!(arg.as_bytes(), [drive, b':', b'\\' | b'/', ..] if drive.is_ascii_alphabetic())
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(env::var("HOME").unwrap_or_default()).join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+fn check_string_map(
+    pointer: &str,
+    field: &str,
+    value: Option<&JsonValue>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let field_pointer = format!("{}/{}", pointer, field);
+    match value {
+        None | Some(JsonValue::Null) => {}
+        Some(JsonValue::Object(map)) => {
+            for (key, value) in map {
+                if !value.is_string() {
+                    diagnostics.push(Diagnostic::error(
+                        format!("{}/{}", field_pointer, escape_pointer_token(key)),
+                        Message::ValueNotString,
+                        &[("field", field), ("key", key)],
+                    ));
+                }
+            }
+        }
+        Some(_) => diagnostics.push(Diagnostic::error(
+            field_pointer,
+            Message::FieldNotStringMap,
+            &[("field", field)],
+        )),
+    }
+}
+
+fn validate_stdio_server(
+    pointer: &str,
+    server: &Map<String, JsonValue>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    match server.get("command") {
+        Some(JsonValue::String(command)) if command.trim().is_empty() => {
+            diagnostics.push(Diagnostic::error(
+                format!("{}/command", pointer),
+                Message::CommandEmpty,
+                &[],
+            ));
+        }
+        Some(JsonValue::String(command)) => {
+            // Claude 启动时的 PATH 可能与本程序不同，因此只给出警告
+            if resolve_command(command).is_none() {
+                diagnostics.push(Diagnostic::warning(
+                    format!("{}/command", pointer),
+                    Message::CommandNotOnPath,
+                    &[("command", command)],
+                ));
+            }
+        }
+        Some(_) => diagnostics.push(Diagnostic::error(
+            format!("{}/command", pointer),
+            Message::CommandNotString,
+            &[],
+        )),
+        None => diagnostics.push(Diagnostic::error(
+            pointer.to_string(),
+            Message::CommandOrUrlMissing,
+            &[],
+        )),
+    }
+
+    match server.get("args") {
+        None => {}
+        Some(JsonValue::Array(args)) => {
+            for (index, arg) in args.iter().enumerate() {
+                let arg_pointer = format!("{}/args/{}", pointer, index);
+                match arg.as_str() {
+                    Some(arg) if looks_like_path(arg) && !expand_home(arg).exists() => {
+                        diagnostics.push(Diagnostic::warning(
+                            arg_pointer,
+                            Message::ArgPathNotFound,
+                            &[("path", arg)],
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        diagnostics.push(Diagnostic::error(arg_pointer, Message::ArgNotString, &[]))
+                    }
+                }
+            }
+        }
+        Some(_) => diagnostics.push(Diagnostic::error(
+            format!("{}/args", pointer),
+            Message::ArgsNotStringArray,
+            &[],
+        )),
+    }
+
+    check_string_map(pointer, "env", server.get("env"), diagnostics);
+
+    if let Some(cwd) = server.get("cwd") {
+        match cwd.as_str() {
+            // 与参数中的路径一样只给出警告，目录可能之后才创建，
+            // 也不能因为其它 server 的旧目录而拒绝保存整个配置
+            Some(cwd) if !expand_home(cwd).is_dir() => diagnostics.push(Diagnostic::warning(
+                format!("{}/cwd", pointer),
+                Message::CwdNotFound,
+                &[("path", cwd)],
+            )),
+            Some(_) => {}
+            None => diagnostics.push(Diagnostic::error(
+                format!("{}/cwd", pointer),
+                Message::CwdNotString,
+                &[],
+            )),
+        }
+    }
+}
+
+fn validate_remote_server(
+    pointer: &str,
+    server: &Map<String, JsonValue>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let url_pointer = format!("{}/url", pointer);
+    match server.get("url").and_then(|v| v.as_str()) {
+        Some(url) => match reqwest::Url::parse(url) {
+            Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => {}
+            Ok(parsed) => diagnostics.push(Diagnostic::error(
+                url_pointer,
+                Message::UrlSchemeUnsupported,
+                &[("scheme", parsed.scheme())],
+            )),
+            Err(e) => diagnostics.push(Diagnostic::error(
+                url_pointer,
+                Message::UrlInvalid,
+                &[("error", &e.to_string())],
+            )),
+        },
+        None => diagnostics.push(Diagnostic::error(url_pointer, Message::UrlNotString, &[])),
+    }
+
+    if let Some(kind) = server.get("type") {
+        if !matches!(kind.as_str(), Some("sse") | Some("http")) {
+            diagnostics.push(Diagnostic::error(
+                format!("{}/type", pointer),
+                Message::RemoteTypeInvalid,
+                &[],
+            ));
+        }
+    }
+
+    check_string_map(pointer, "headers", server.get("headers"), diagnostics);
+}
+
+// servers_key 为客户端配置中保存 server 列表的字段，例如 mcpServers
+fn server_pointer(servers_key: &str, name: &str) -> String {
+    format!(
+        "/{}/{}",
+        escape_pointer_token(servers_key),
+        escape_pointer_token(name)
+    )
+}
+
+fn validate_server(
+    servers_key: &str,
+    name: &str,
+    server: &JsonValue,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let pointer = server_pointer(servers_key, name);
+
+    let Some(server) = server.as_object() else {
+        diagnostics.push(Diagnostic::error(
+            pointer,
+            Message::ServerEntryNotObject,
+            &[],
+        ));
+        return;
+    };
+
+    if server.contains_key("url") && !server.contains_key("command") {
+        validate_remote_server(&pointer, server, diagnostics);
+    } else {
+        validate_stdio_server(&pointer, server, diagnostics);
+    }
+}
+
+fn check_case_duplicates<'a>(
+    servers_key: &str,
+    names: impl Iterator<Item = &'a String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut seen: HashMap<String, &String> = HashMap::new();
+    for name in names {
+        if let Some(first) = seen.get(&name.to_lowercase()) {
+            diagnostics.push(Diagnostic::error(
+                server_pointer(servers_key, name),
+                Message::ServerNameCaseConflict,
+                &[("name", name), ("other", first)],
+            ));
+        } else {
+            seen.insert(name.to_lowercase(), name);
+        }
+    }
+}
+
+// 检查整个配置文件
+pub fn validate_document(servers_key: &str, document: &JsonValue) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let Some(root) = document.as_object() else {
+        diagnostics.push(Diagnostic::error(
+            String::new(),
+            Message::ConfigNotObject,
+            &[],
+        ));
+        return diagnostics;
+    };
+
+    match root.get(servers_key) {
+        None => {}
+        Some(JsonValue::Object(servers)) => {
+            for (name, server) in servers {
+                validate_server(servers_key, name, server, &mut diagnostics);
+            }
+            check_case_duplicates(servers_key, servers.keys(), &mut diagnostics);
+        }
+        Some(_) => diagnostics.push(Diagnostic::error(
+            format!("/{}", escape_pointer_token(servers_key)),
+            Message::ServersNotObject,
+            &[("key", servers_key)],
+        )),
+    }
+
+    diagnostics
+}
+
+// 检查即将写入的单个 server，other_names 为配置中已有的其它 server
+pub fn validate_server_entry<'a>(
+    servers_key: &str,
+    name: &str,
+    entry: &McpServerEntry,
+    other_names: impl Iterator<Item = &'a String>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    match serde_json::to_value(entry) {
+        Ok(value) => validate_server(servers_key, name, &value, &mut diagnostics),
+        Err(e) => diagnostics.push(Diagnostic::error(
+            server_pointer(servers_key, name),
+            Message::ServerEntryInvalid,
+            &[("error", &e.to_string())],
+        )),
+    }
+
+    let lowercase_name = name.to_lowercase();
+    if let Some(existing) = other_names
+        .filter(|other| other.as_str() != name)
+        .find(|other| other.to_lowercase() == lowercase_name)
+    {
+        diagnostics.push(Diagnostic::error(
+            server_pointer(servers_key, name),
+            Message::ServerNameCaseConflict,
+            &[("name", name), ("other", existing)],
+        ));
+    }
+
+    diagnostics
+}
+
+// 有错误级别的诊断时拒绝保存
+pub fn ensure_valid(diagnostics: &[Diagnostic]) -> Result<(), AppError> {
+    let errors: Vec<&Diagnostic> = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .collect();
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let messages: Vec<String> = errors
+        .iter()
+        .map(|d| format!("{}: {}", d.pointer, d.message))
+        .collect();
+    Err(AppError::localized(
+        ErrorCode::ConfigInvalid,
+        Message::ConfigInvalid,
+        &[("errors", &messages.join("; "))],
+    )
+    .with("diagnostics", serde_json::json!(errors)))
+}
+
+#[tauri::command]
+pub fn validate_claude_config() -> Result<Vec<Diagnostic>, AppError> {
+    let config_path = crate::claude_config::config_file_path()?;
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        // 还没有配置文件时没有需要检查的内容
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AppError::io(Message::ConfigReadFailed, &config_path, e)),
+    };
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match serde_json::from_str::<JsonValue>(&content) {
+        Ok(document) => Ok(validate_document(
+            McpClient::ClaudeDesktop.servers_key(),
+            &document,
+        )),
+        Err(e) => Ok(vec![Diagnostic::error(
+            String::new(),
+            Message::JsonSyntaxError,
+            &[
+                ("line", &e.line().to_string()),
+                ("column", &e.column().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn builds_pointers_from_the_servers_key() {
+        let document = json!({ "servers": { "a/b": { "command": "" } } });
+        let diagnostics = validate_document("servers", &document);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pointer, "/servers/a~1b/command");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn missing_cwd_does_not_block_saving() {
+        let document = json!({
+            "mcpServers": { "old": { "command": "node", "cwd": "/definitely/not/here" } }
+        });
+        let diagnostics = validate_document("mcpServers", &document);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(ensure_valid(&diagnostics).is_ok());
+    }
+
+    #[test]
+    fn rejects_names_that_only_differ_by_case() {
+        let document = json!({
+            "mcpServers": {
+                "github": { "url": "https://example.com/mcp" },
+                "GitHub": { "url": "https://example.com/mcp" }
+            }
+        });
+        let diagnostics = validate_document("mcpServers", &document);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pointer, "/mcpServers/GitHub");
+        assert!(ensure_valid(&diagnostics).is_err());
+    }
+
+    #[test]
+    fn checks_remote_urls() {
+        let document = json!({ "mcpServers": { "r": { "url": "ftp://example.com" } } });
+        let diagnostics = validate_document("mcpServers", &document);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pointer, "/mcpServers/r/url");
+    }
+}
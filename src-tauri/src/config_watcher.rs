@@ -0,0 +1,299 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+pub const CONFIG_CHANGED_EVENT: &str = "claude-config-changed";
+pub const CONFIG_WATCHER_STATUS_EVENT: &str = "claude-config-watcher-status";
+
+// 合并短时间内的多次文件事件，例如编辑器先截断再写入
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// 当前的 watcher，替换后旧的 watcher 被释放，对应的线程随之退出
+static CONFIG_WATCHER: Lazy<Mutex<Option<RecommendedWatcher>>> = Lazy::new(|| Mutex::new(None));
+
+// 启动失败后重试的间隔，每次失败翻倍
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+// 最近一次启动失败的原因，成功后清空
+static WATCHER_ERROR: Lazy<Mutex<Option<AppError>>> = Lazy::new(|| Mutex::new(None));
+
+// 每次调用 ensure_config_watcher 加一，旧的重试线程发现变化后退出
+static WATCHER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigWatcherStatus {
+    pub watching: bool,
+    pub error: Option<AppError>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigChangeSummary {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    // mcpServers 以外的字段是否有变化
+    pub settings_changed: bool,
+    // 新内容无法解析时的错误信息
+    pub error: Option<AppError>,
+}
+
+impl ConfigChangeSummary {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && !self.settings_changed
+            && self.error.is_none()
+    }
+}
+
+fn read_document(path: &Path) -> Result<Map<String, JsonValue>, AppError> {
+    let content =
+        fs::read_to_string(path).map_err(|e| AppError::io(Message::ConfigReadFailed, path, e))?;
+    serde_json::from_str(&content).map_err(|e| AppError::config_parse(path, e))
+}
+
+fn watch_error(e: notify::Error) -> AppError {
+    AppError::localized(
+        ErrorCode::Io,
+        Message::ConfigWatchFailed,
+        &[("error", &e.to_string())],
+    )
+}
+
+fn invalid_config_path(path: &Path) -> AppError {
+    AppError::localized(
+        ErrorCode::ConfigPathInvalid,
+        Message::ConfigPathInvalid,
+        &[("path", &path.display().to_string())],
+    )
+    .with("path", path.to_string_lossy().to_string())
+}
+
+fn set_watcher(watcher: RecommendedWatcher) -> Result<(), AppError> {
+    *CONFIG_WATCHER
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock config watcher: {}", e)))? =
+        Some(watcher);
+    Ok(())
+}
+
+fn servers_of(document: &Map<String, JsonValue>) -> Map<String, JsonValue> {
+    document
+        .get("mcpServers")
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn summarize(
+    before: &Map<String, JsonValue>,
+    after: &Map<String, JsonValue>,
+) -> ConfigChangeSummary {
+    let old_servers = servers_of(before);
+    let new_servers = servers_of(after);
+
+    let mut summary = ConfigChangeSummary::default();
+    for (name, old_value) in &old_servers {
+        match new_servers.get(name) {
+            Some(new_value) if new_value != old_value => summary.modified.push(name.clone()),
+            Some(_) => {}
+            None => summary.removed.push(name.clone()),
+        }
+    }
+    for name in new_servers.keys() {
+        if !old_servers.contains_key(name) {
+            summary.added.push(name.clone());
+        }
+    }
+
+    let without_servers = |document: &Map<String, JsonValue>| {
+        let mut document = document.clone();
+        document.shift_remove("mcpServers");
+        document
+    };
+    summary.settings_changed = without_servers(before) != without_servers(after);
+
+    summary
+}
+
+// 最近的已存在的目录，可能是 dir 本身
+fn nearest_existing_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+// 配置目录还不存在时（例如没有安装 Claude Desktop）监听最近的上级目录，
+// 不主动创建目录；目录出现后重新启动监听
+fn watch_ancestor<R: Runtime>(
+    app: AppHandle<R>,
+    config_dir: PathBuf,
+    ancestor: PathBuf,
+) -> Result<(), AppError> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    watcher
+        .watch(&ancestor, RecursiveMode::NonRecursive)
+        .map_err(watch_error)?;
+    set_watcher(watcher)?;
+
+    std::thread::spawn(move || loop {
+        // 开始监听前目录可能已经创建，所以先检查一次
+        if nearest_existing_dir(&config_dir).as_ref() != Some(&ancestor) {
+            ensure_config_watcher(app);
+            return;
+        }
+        if rx.recv().is_err() {
+            return;
+        }
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+    });
+
+    Ok(())
+}
+
+// 监听配置文件所在目录，文件变化时通知前端并刷新托盘菜单；
+// 配置文件路径改变后再次调用即可切换到新的目录
+fn start_config_watcher<R: Runtime>(app: AppHandle<R>) -> Result<(), AppError> {
+    let config_path = crate::claude_config::config_file_path()?;
+    let watch_dir = config_path
+        .parent()
+        .ok_or_else(|| invalid_config_path(&config_path))?
+        .to_path_buf();
+    let file_name = config_path.file_name().map(|name| name.to_os_string());
+
+    // 配置目录在第一次修改配置时才创建
+    if !watch_dir.is_dir() {
+        let ancestor =
+            nearest_existing_dir(&watch_dir).ok_or_else(|| invalid_config_path(&config_path))?;
+        return watch_ancestor(app, watch_dir, ancestor);
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+
+    // 配置文件是通过重命名原子替换的，所以监听目录而不是文件本身
+    watcher
+        .watch(&watch_dir, RecursiveMode::NonRecursive)
+        .map_err(watch_error)?;
+    set_watcher(watcher)?;
+
+    std::thread::spawn(move || {
+        let mut last_document = read_document(&config_path).unwrap_or_default();
+
+        let is_config_event = |event: &notify::Result<notify::Event>| match event {
+            Ok(event) => event
+                .paths
+                .iter()
+                .any(|path| path.file_name().map(|name| name.to_os_string()) == file_name),
+            Err(_) => false,
+        };
+
+        while let Ok(event) = rx.recv() {
+            if !is_config_event(&event) {
+                continue;
+            }
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+            let summary = match read_document(&config_path) {
+                Ok(document) => {
+                    let summary = summarize(&last_document, &document);
+                    last_document = document;
+                    summary
+                }
+                // 文件被删除或正在写入时可能读取失败
+                Err(e) => ConfigChangeSummary {
+                    error: Some(e),
+                    ..Default::default()
+                },
+            };
+
+            if summary.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = app.emit(CONFIG_CHANGED_EVENT, &summary) {
+                eprintln!("Failed to emit config change event: {}", e);
+            }
+            if let Err(e) = crate::tray::update_tray_status(app.clone()) {
+                eprintln!("Failed to update tray status: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn watcher_status() -> ConfigWatcherStatus {
+    let error = WATCHER_ERROR.lock().ok().and_then(|error| error.clone());
+    ConfigWatcherStatus {
+        watching: error.is_none()
+            && CONFIG_WATCHER
+                .lock()
+                .map(|watcher| watcher.is_some())
+                .unwrap_or(false),
+        error,
+    }
+}
+
+fn set_watcher_error<R: Runtime>(app: &AppHandle<R>, error: Option<AppError>) {
+    if let Ok(mut current) = WATCHER_ERROR.lock() {
+        *current = error;
+    }
+    if let Err(e) = app.emit(CONFIG_WATCHER_STATUS_EVENT, watcher_status()) {
+        eprintln!("Failed to emit config watcher status: {}", e);
+    }
+}
+
+// 启动监听；失败时通知前端，并在后台重试直到成功或再次调用本函数
+pub fn ensure_config_watcher<R: Runtime>(app: AppHandle<R>) {
+    let generation = WATCHER_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let error = match start_config_watcher(app.clone()) {
+        Ok(()) => {
+            set_watcher_error(&app, None);
+            return;
+        }
+        Err(e) => e,
+    };
+    eprintln!("Failed to start config watcher: {}", error);
+    set_watcher_error(&app, Some(error));
+
+    std::thread::spawn(move || {
+        let mut interval = RETRY_INTERVAL;
+        loop {
+            std::thread::sleep(interval);
+            if WATCHER_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            match start_config_watcher(app.clone()) {
+                Ok(()) => {
+                    set_watcher_error(&app, None);
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("Failed to start config watcher: {}", e);
+                    set_watcher_error(&app, Some(e));
+                    interval = (interval * 2).min(MAX_RETRY_INTERVAL);
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_config_watcher_status() -> ConfigWatcherStatus {
+    watcher_status()
+}
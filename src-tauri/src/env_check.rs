@@ -1,3 +1,5 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::{t, Message};
 use serde::Serialize;
 use std::env;
 use std::path::PathBuf;
@@ -22,7 +24,7 @@ pub async fn get_python_path(app_handle: AppHandle) -> EnvCheckResult {
 
     match shell
         .command(python_exec)
-        .args(&["--version"])
+        .args(["--version"])
         .output()
         .await
     {
@@ -33,7 +35,7 @@ pub async fn get_python_path(app_handle: AppHandle) -> EnvCheckResult {
         },
         _ => EnvCheckResult {
             is_installed: false,
-            version: "未安装".to_string(),
+            version: t(Message::NotInstalled).to_string(),
             install_url: "https://www.python.org/downloads/".to_string(),
         },
     }
@@ -43,7 +45,7 @@ pub async fn get_python_path(app_handle: AppHandle) -> EnvCheckResult {
 pub async fn get_node_path(app_handle: AppHandle) -> EnvCheckResult {
     let shell = app_handle.shell();
 
-    match shell.command("node").args(&["--version"]).output().await {
+    match shell.command("node").args(["--version"]).output().await {
         Ok(output) if output.status.success() => EnvCheckResult {
             is_installed: true,
             version: String::from_utf8_lossy(&output.stdout).trim().to_string(),
@@ -51,7 +53,7 @@ pub async fn get_node_path(app_handle: AppHandle) -> EnvCheckResult {
         },
         _ => EnvCheckResult {
             is_installed: false,
-            version: "未安装".to_string(),
+            version: t(Message::NotInstalled).to_string(),
             install_url: "https://nodejs.org/".to_string(),
         },
     }
@@ -61,7 +63,7 @@ pub async fn get_node_path(app_handle: AppHandle) -> EnvCheckResult {
 pub async fn get_uv_path(app_handle: AppHandle) -> EnvCheckResult {
     let shell = app_handle.shell();
 
-    match shell.command("uv").args(&["--version"]).output().await {
+    match shell.command("uv").args(["--version"]).output().await {
         Ok(output) if output.status.success() => EnvCheckResult {
             is_installed: true,
             version: String::from_utf8_lossy(&output.stdout).trim().to_string(),
@@ -69,17 +71,40 @@ pub async fn get_uv_path(app_handle: AppHandle) -> EnvCheckResult {
         },
         _ => EnvCheckResult {
             is_installed: false,
-            version: "未安装".to_string(),
+            version: t(Message::NotInstalled).to_string(),
             install_url: "https://github.com/astral-sh/uv".to_string(),
         },
     }
 }
 
+#[cfg(target_os = "macos")]
+fn install_failed(env_type: &str) -> AppError {
+    AppError::localized(
+        ErrorCode::EnvInstallFailed,
+        Message::EnvInstallFailed,
+        &[("env", env_type)],
+    )
+    .with("env_type", env_type)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn unsupported_platform(env_type: &str) -> AppError {
+    AppError::localized(
+        ErrorCode::UnsupportedPlatform,
+        Message::UnsupportedPlatform,
+        &[("env", env_type)],
+    )
+    .with("env_type", env_type)
+    .with("platform", std::env::consts::OS)
+}
+
 #[tauri::command]
 pub async fn install_environment(
     app_handle: AppHandle,
     env_type: &str,
-) -> Result<EnvCheckResult, String> {
+) -> Result<EnvCheckResult, AppError> {
+    // 只有 macOS 支持自动安装，其它平台用不到 shell
+    #[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
     let shell = app_handle.shell();
 
     match env_type {
@@ -88,7 +113,7 @@ pub async fn install_environment(
             {
                 match shell
                     .command("brew")
-                    .args(&["install", "python@3.11"])
+                    .args(["install", "python@3.11"])
                     .output()
                     .await
                 {
@@ -96,12 +121,12 @@ pub async fn install_environment(
                         // 安装成功后，重新检查状态
                         Ok(get_python_path(app_handle).await)
                     }
-                    _ => Err("安装失败".to_string()),
+                    _ => Err(install_failed(env_type)),
                 }
             }
             #[cfg(not(target_os = "macos"))]
             {
-                Err("暂不支持此系统的自动安装".to_string())
+                Err(unsupported_platform(env_type))
             }
         }
         "node" => {
@@ -109,7 +134,7 @@ pub async fn install_environment(
             {
                 match shell
                     .command("brew")
-                    .args(&["install", "node@20"])
+                    .args(["install", "node@20"])
                     .output()
                     .await
                 {
@@ -117,36 +142,36 @@ pub async fn install_environment(
                         // 安装成功后，重新检查状态
                         Ok(get_node_path(app_handle).await)
                     }
-                    _ => Err("安装失败".to_string()),
+                    _ => Err(install_failed(env_type)),
                 }
             }
             #[cfg(not(target_os = "macos"))]
             {
-                Err("暂不支持此系统的自动安装".to_string())
+                Err(unsupported_platform(env_type))
             }
         }
         "uv" => {
             #[cfg(target_os = "macos")]
             {
-                match shell
-                    .command("brew")
-                    .args(&["install", "uv"])
-                    .output()
-                    .await
-                {
+                match shell.command("brew").args(["install", "uv"]).output().await {
                     Ok(output) if output.status.success() => {
                         // 安装成功后，重新检查状态
                         Ok(get_uv_path(app_handle).await)
                     }
-                    _ => Err("安装失败".to_string()),
+                    _ => Err(install_failed(env_type)),
                 }
             }
             #[cfg(not(target_os = "macos"))]
             {
-                Err("暂不支持此系统的自动安装".to_string())
+                Err(unsupported_platform(env_type))
             }
         }
-        _ => Err("不支持的环境类型".to_string()),
+        _ => Err(AppError::localized(
+            ErrorCode::UnsupportedEnvironment,
+            Message::UnsupportedEnvironment,
+            &[("env", env_type)],
+        )
+        .with("env_type", env_type)),
     }
 }
 
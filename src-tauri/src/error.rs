@@ -0,0 +1,141 @@
+use crate::i18n::{t_args, Message};
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use std::fmt;
+use std::path::Path;
+
+// 错误码，前端根据它区分错误类型，名称确定后不要修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    // 配置文件
+    ConfigParse,
+    ConfigConflict,
+    ConfigInvalid,
+    ConfigUnsupported,
+    ConfigPathInvalid,
+    // MCP server
+    ServerNotFound,
+    ServerAlreadyInstalled,
+    ServerAlreadyDisabled,
+    ServerNotDisabled,
+    ServerIsRemote,
+    ServerStartFailed,
+    ServerNotRunning,
+    RemoteConnectFailed,
+    // MCP 协议
+    RpcError,
+    RequestTimeout,
+    InvalidArguments,
+    // 备份
+    BackupNotFound,
+    BackupIdInvalid,
+    // profile
+    ProfileNotFound,
+    ProfileInvalid,
+    // 密钥和变量
+    SecretNotFound,
+    SecretAlreadyExists,
+    SecretNameInvalid,
+    SecretInUse,
+    SecretCorrupted,
+    VaultKeyInvalid,
+    VariableNotFound,
+    VariableNameInvalid,
+    VariableUnresolved,
+    // 运行环境
+    ProcessFailed,
+    // 只有 macOS 支持自动安装
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    EnvInstallFailed,
+    UnsupportedEnvironment,
+    UnsupportedPlatform,
+    // 导入导出
+    BundleInvalid,
+    // 其它
+    HomeNotFound,
+    Io,
+    Store,
+    Network,
+    Api,
+    Internal,
+}
+
+// 返回给前端的错误：code 不变，message 给人看，context 放相关的路径、名称等
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub context: Map<String, JsonValue>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        AppError {
+            code,
+            message: message.into(),
+            context: Map::new(),
+        }
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<JsonValue>) -> Self {
+        self.context.insert(key.to_string(), value.into());
+        self
+    }
+
+    // message 使用当前语言，args 替换其中的占位符
+    pub fn localized(code: ErrorCode, message: Message, args: &[(&str, &str)]) -> Self {
+        AppError::new(code, t_args(message, args))
+    }
+
+    // 内部错误不翻译
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::new(ErrorCode::Internal, message)
+    }
+
+    pub fn io(message: Message, path: &Path, e: std::io::Error) -> Self {
+        AppError::localized(ErrorCode::Io, message, &[("error", &e.to_string())])
+            .with("path", path_value(path))
+    }
+
+    pub fn config_parse(path: &Path, e: serde_json::Error) -> Self {
+        AppError::localized(
+            ErrorCode::ConfigParse,
+            Message::ConfigParseFailed,
+            &[("error", &e.to_string())],
+        )
+        .with("path", path_value(path))
+        .with("line", e.line())
+        .with("column", e.column())
+    }
+
+    pub fn home_not_found(variable: &str) -> Self {
+        AppError::localized(
+            ErrorCode::HomeNotFound,
+            Message::HomeNotFound,
+            &[("variable", variable)],
+        )
+        .with("variable", variable)
+    }
+
+    pub fn server_not_found(name: &str) -> Self {
+        AppError::localized(
+            ErrorCode::ServerNotFound,
+            Message::ServerNotFound,
+            &[("server", name)],
+        )
+        .with("server", name)
+    }
+}
+
+fn path_value(path: &Path) -> JsonValue {
+    JsonValue::String(path.to_string_lossy().to_string())
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
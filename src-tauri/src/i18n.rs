@@ -0,0 +1,769 @@
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::RwLock;
+use tauri::{AppHandle, Runtime};
+
+// 后端消息支持的语言，与 README 保持一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    Zh,
+    Ja,
+}
+
+// 当前使用的语言，启动时根据设置或系统语言初始化
+static LANGUAGE: Lazy<RwLock<Language>> = Lazy::new(|| RwLock::new(system_language()));
+
+impl Language {
+    // 识别 "zh_CN.UTF-8"、"ja-JP"、"en" 这类 locale
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale
+            .split(['_', '-', '.', '@'])
+            .next()?
+            .to_ascii_lowercase();
+        match code.as_str() {
+            "en" => Some(Language::En),
+            "zh" => Some(Language::Zh),
+            "ja" => Some(Language::Ja),
+            _ => None,
+        }
+    }
+}
+
+// 系统语言，无法识别时使用英文
+pub fn system_language() -> Language {
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Some(language) = env::var(var)
+            .ok()
+            .and_then(|locale| Language::from_locale(&locale))
+        {
+            return language;
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(language) = windows_locale().as_deref().and_then(Language::from_locale) {
+            return language;
+        }
+    }
+
+    // 从 Finder 启动的 app 通常没有 LANG，读取系统偏好设置
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleLocale"])
+            .output()
+        {
+            if let Some(language) =
+                Language::from_locale(String::from_utf8_lossy(&output.stdout).trim())
+            {
+                return language;
+            }
+        }
+    }
+
+    Language::En
+}
+
+// Windows 一般不设置 LANG，读取用户的区域设置，例如 "zh-CN"
+#[cfg(target_os = "windows")]
+fn windows_locale() -> Option<String> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetUserDefaultLocaleName(name: *mut u16, len: i32) -> i32;
+    }
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    // 返回值包含结尾的 0，失败时为 0
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    if len <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+pub fn current_language() -> Language {
+    LANGUAGE
+        .read()
+        .map(|language| *language)
+        .unwrap_or(Language::En)
+}
+
+fn set_current_language(language: Language) {
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = language;
+    }
+}
+
+// 后端显示给用户的文本
+#[derive(Debug, Clone, Copy)]
+pub enum Message {
+    // 托盘
+    TrayRunning,
+    TrayStopped,
+    TrayShowApp,
+    TrayQuit,
+    TrayProfiles,
+    // 环境检查
+    NotInstalled,
+    // 只有 macOS 支持自动安装
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    EnvInstallFailed,
+    UnsupportedEnvironment,
+    UnsupportedPlatform,
+    // 配置文件
+    ConfigParseFailed,
+    ConfigReadFailed,
+    ConfigWriteFailed,
+    ConfigDirCreateFailed,
+    ConfigConflict,
+    ConfigFieldChanged,
+    ConfigFieldMissing,
+    JournalApplyFailed,
+    ConfigInvalid,
+    ConfigHasComments,
+    ConfigPathNotAbsolute,
+    ConfigPathInvalid,
+    ConfigWatchFailed,
+    WorkspaceNotSet,
+    HomeNotFound,
+    // MCP server
+    ServerNotFound,
+    ServerConfigError,
+    ServerAlreadyDisabled,
+    ServerNotDisabled,
+    ServerIsRemote,
+    ServerStartFailed,
+    ServerExited,
+    ServerNotRunning,
+    RpcError,
+    InvalidRpcResponse,
+    PaginationNotFinished,
+    RequestTimeout,
+    ToolArgumentsNotObject,
+    RemoteConnectFailed,
+    RemoteBadStatus,
+    TemplatesFetchFailed,
+    TemplatesParseFailed,
+    ClaudeKillFailed,
+    ClaudeStartFailed,
+    // server 日志
+    LogReadFailed,
+    LogWriteFailed,
+    // 配置快照
+    BackupIdInvalid,
+    BackupNotFound,
+    BackupRetentionInvalid,
+    BackupReadFailed,
+    BackupWriteFailed,
+    // 配置检查
+    ValueNotString,
+    FieldNotStringMap,
+    CommandEmpty,
+    CommandNotOnPath,
+    CommandNotString,
+    CommandOrUrlMissing,
+    ArgPathNotFound,
+    ArgNotString,
+    ArgsNotStringArray,
+    CwdNotFound,
+    CwdNotString,
+    UrlSchemeUnsupported,
+    UrlInvalid,
+    UrlNotString,
+    RemoteTypeInvalid,
+    ServerEntryNotObject,
+    ServerEntryInvalid,
+    ServerNameCaseConflict,
+    ConfigNotObject,
+    ServersNotObject,
+    FieldNotArray,
+    JsonSyntaxError,
+    // 配置方案
+    ProfileNotFound,
+    ProfileServersNotInstalled,
+    ProfileNameEmpty,
+    // 密钥和变量
+    SecretNotFound,
+    SecretAlreadyExists,
+    SecretNameInvalid,
+    SecretInUse,
+    SecretCorrupted,
+    SecretValueNotSet,
+    SecretValueIsReference,
+    VaultKeyMismatch,
+    AppDataDirFailed,
+    VaultKeyInvalid,
+    VaultKeyReadFailed,
+    VaultKeyWriteFailed,
+    VariablesUnresolved,
+    VariableBuiltIn,
+    VariableNameInvalid,
+    VariableNotFound,
+    // server 导入
+    ServersAlreadyInstalled,
+    PlaceholdersMissing,
+    PlaceholderMissing,
+    BundleReadFailed,
+    BundleWriteFailed,
+    BundleParseFailed,
+    BundleVersionUnsupported,
+    // store
+    StoreOpenFailed,
+    StoreReadFailed,
+    StoreSaveFailed,
+}
+
+impl Message {
+    // 依次为 en、zh、ja，{name} 形式的占位符由 t_args 替换
+    fn texts(self) -> [&'static str; 3] {
+        match self {
+            Message::TrayRunning => ["Running", "运行中", "実行中"],
+            Message::TrayStopped => ["Stopped", "已停止", "停止中"],
+            Message::TrayShowApp => ["Show App", "显示窗口", "ウィンドウを表示"],
+            Message::TrayQuit => ["Quit", "退出", "終了"],
+            Message::TrayProfiles => ["Profiles", "配置方案", "プロファイル"],
+            Message::NotInstalled => ["Not installed", "未安装", "未インストール"],
+            Message::EnvInstallFailed => [
+                "Failed to install {env}",
+                "安装 {env} 失败",
+                "{env} のインストールに失敗しました",
+            ],
+            Message::UnsupportedEnvironment => [
+                "Unsupported environment type: {env}",
+                "不支持的环境类型：{env}",
+                "サポートされていない環境です: {env}",
+            ],
+            Message::UnsupportedPlatform => [
+                "Automatic installation of {env} is not supported on this system",
+                "暂不支持在此系统上自动安装 {env}",
+                "このシステムでは {env} の自動インストールに対応していません",
+            ],
+            Message::ConfigParseFailed => [
+                "Failed to parse config: {error}",
+                "解析配置文件失败：{error}",
+                "設定ファイルの解析に失敗しました: {error}",
+            ],
+            Message::ConfigReadFailed => [
+                "Failed to read config file: {error}",
+                "读取配置文件失败：{error}",
+                "設定ファイルの読み込みに失敗しました: {error}",
+            ],
+            Message::ConfigWriteFailed => [
+                "Failed to write config file: {error}",
+                "写入配置文件失败：{error}",
+                "設定ファイルの書き込みに失敗しました: {error}",
+            ],
+            Message::ConfigDirCreateFailed => [
+                "Failed to create config directory: {error}",
+                "创建配置目录失败：{error}",
+                "設定ディレクトリの作成に失敗しました: {error}",
+            ],
+            Message::ConfigConflict => [
+                "Config file was modified by another program since it was read",
+                "配置文件在读取后被其他程序修改",
+                "設定ファイルは読み込み後に別のプログラムによって変更されました",
+            ],
+            Message::ConfigFieldChanged => [
+                "'{path}' was changed by another program",
+                "'{path}' 已被其他程序修改",
+                "'{path}' は別のプログラムによって変更されました",
+            ],
+            Message::ConfigFieldMissing => [
+                "'{path}' is missing from the config",
+                "配置中缺少 '{path}'",
+                "設定に '{path}' がありません",
+            ],
+            Message::JournalApplyFailed => [
+                "Cannot apply '{description}' to {client}: {error}",
+                "无法将 '{description}' 应用到 {client}：{error}",
+                "'{description}' を {client} に適用できません: {error}",
+            ],
+            Message::ConfigInvalid => [
+                "Config validation failed: {errors}",
+                "配置校验失败：{errors}",
+                "設定の検証に失敗しました: {errors}",
+            ],
+            Message::ConfigHasComments => [
+                "{client} settings contain comments that would be lost; please edit {path} manually",
+                "{client} 的设置中包含注释，自动修改会丢失注释，请手动编辑 {path}",
+                "{client} の設定にはコメントが含まれており失われるため、{path} を手動で編集してください",
+            ],
+            Message::ConfigPathNotAbsolute => [
+                "Config path must be absolute: {path}",
+                "配置文件路径必须是绝对路径：{path}",
+                "設定ファイルのパスは絶対パスで指定してください: {path}",
+            ],
+            Message::ConfigPathInvalid => [
+                "Invalid config path: {path}",
+                "无效的配置路径：{path}",
+                "無効な設定パスです: {path}",
+            ],
+            Message::ConfigWatchFailed => [
+                "Failed to watch config directory: {error}",
+                "监听配置目录失败：{error}",
+                "設定ディレクトリの監視に失敗しました: {error}",
+            ],
+            Message::WorkspaceNotSet => [
+                "Set a workspace directory before using the {client} workspace config",
+                "使用 {client} 的工作区配置前请先设置工作区目录",
+                "{client} のワークスペース設定を使う前にワークスペースディレクトリを設定してください",
+            ],
+            Message::HomeNotFound => [
+                "Could not find {variable} directory",
+                "找不到 {variable} 目录",
+                "{variable} ディレクトリが見つかりません",
+            ],
+            Message::ServerNotFound => [
+                "MCP server '{server}' not found",
+                "找不到 MCP server '{server}'",
+                "MCP server '{server}' が見つかりません",
+            ],
+            Message::ServerConfigError => [
+                "MCP server '{server}': {error}",
+                "MCP server '{server}'：{error}",
+                "MCP server '{server}': {error}",
+            ],
+            Message::ServerAlreadyDisabled => [
+                "MCP server '{server}' is already disabled",
+                "MCP server '{server}' 已被禁用",
+                "MCP server '{server}' はすでに無効です",
+            ],
+            Message::ServerNotDisabled => [
+                "MCP server '{server}' is not disabled",
+                "MCP server '{server}' 未被禁用",
+                "MCP server '{server}' は無効になっていません",
+            ],
+            Message::ServerIsRemote => [
+                "MCP server '{server}' is a remote server and has no local process",
+                "MCP server '{server}' 是远程 server，没有本地进程",
+                "MCP server '{server}' はリモート server のため、ローカルプロセスがありません",
+            ],
+            Message::ServerStartFailed => [
+                "Failed to start MCP server: {error}",
+                "启动 MCP server 失败：{error}",
+                "MCP server の起動に失敗しました: {error}",
+            ],
+            Message::ServerExited => [
+                "MCP server failed to start: process exited with status {status}",
+                "MCP server 启动失败：进程已退出，状态 {status}",
+                "MCP server の起動に失敗しました: プロセスが終了しました (ステータス {status})",
+            ],
+            Message::ServerNotRunning => [
+                "MCP server '{server}' is not running",
+                "MCP server '{server}' 未在运行",
+                "MCP server '{server}' は実行されていません",
+            ],
+            Message::RpcError => [
+                "MCP server returned an error: {error}",
+                "MCP server 返回错误：{error}",
+                "MCP server がエラーを返しました: {error}",
+            ],
+            Message::PaginationNotFinished => [
+                "MCP server kept returning more pages for {method}",
+                "MCP server 的 {method} 分页没有结束",
+                "MCP server の {method} のページングが終わりません",
+            ],
+            Message::InvalidRpcResponse => [
+                "MCP server sent an invalid response: {error}",
+                "MCP server 的响应无效：{error}",
+                "MCP server の応答が無効です: {error}",
+            ],
+            Message::RequestTimeout => [
+                "MCP server did not respond to {method} within {seconds} seconds",
+                "MCP server 在 {seconds} 秒内没有响应 {method}",
+                "MCP server が {seconds} 秒以内に {method} に応答しませんでした",
+            ],
+            Message::ToolArgumentsNotObject => [
+                "Arguments for tool '{tool}' must be a JSON object",
+                "工具 '{tool}' 的参数必须是 JSON 对象",
+                "ツール '{tool}' の引数は JSON オブジェクトである必要があります",
+            ],
+            Message::RemoteConnectFailed => [
+                "Failed to connect to remote MCP server: {error}",
+                "连接远程 MCP server 失败：{error}",
+                "リモート MCP server への接続に失敗しました: {error}",
+            ],
+            Message::RemoteBadStatus => [
+                "Remote MCP server responded with status {status}",
+                "远程 MCP server 返回状态 {status}",
+                "リモート MCP server がステータス {status} を返しました",
+            ],
+            Message::TemplatesFetchFailed => [
+                "Failed to fetch templates: {error}",
+                "获取模板失败：{error}",
+                "テンプレートの取得に失敗しました: {error}",
+            ],
+            Message::TemplatesParseFailed => [
+                "Failed to parse response: {error}",
+                "解析响应失败：{error}",
+                "レスポンスの解析に失敗しました: {error}",
+            ],
+            Message::ClaudeKillFailed => [
+                "Failed to kill Claude",
+                "无法结束 Claude 进程",
+                "Claude を終了できませんでした",
+            ],
+            Message::ClaudeStartFailed => [
+                "Failed to start Claude",
+                "无法启动 Claude",
+                "Claude を起動できませんでした",
+            ],
+            Message::LogReadFailed => [
+                "Failed to read log file: {error}",
+                "读取日志文件失败：{error}",
+                "ログファイルの読み込みに失敗しました: {error}",
+            ],
+            Message::LogWriteFailed => [
+                "Failed to write log file: {error}",
+                "写入日志文件失败：{error}",
+                "ログファイルの書き込みに失敗しました: {error}",
+            ],
+            Message::BackupIdInvalid => [
+                "Invalid backup id: {id}",
+                "无效的快照 ID：{id}",
+                "無効なバックアップ ID です: {id}",
+            ],
+            Message::BackupNotFound => [
+                "Backup '{id}' not found",
+                "找不到快照 '{id}'",
+                "バックアップ '{id}' が見つかりません",
+            ],
+            Message::BackupRetentionInvalid => [
+                "Backup retention must be at least 1",
+                "保留的快照数量至少为 1",
+                "保持するバックアップ数は 1 以上にしてください",
+            ],
+            Message::BackupReadFailed => [
+                "Failed to read backup: {error}",
+                "读取快照失败：{error}",
+                "バックアップの読み込みに失敗しました: {error}",
+            ],
+            Message::BackupWriteFailed => [
+                "Failed to write backup: {error}",
+                "写入快照失败：{error}",
+                "バックアップの書き込みに失敗しました: {error}",
+            ],
+            Message::ValueNotString => [
+                "Value of {field} '{key}' must be a string",
+                "{field} 中 '{key}' 的值必须是字符串",
+                "{field} の '{key}' の値は文字列である必要があります",
+            ],
+            Message::FieldNotStringMap => [
+                "'{field}' must be an object of strings",
+                "'{field}' 必须是值为字符串的对象",
+                "'{field}' は値が文字列のオブジェクトである必要があります",
+            ],
+            Message::CommandEmpty => [
+                "Command must not be empty",
+                "命令不能为空",
+                "コマンドを空にすることはできません",
+            ],
+            Message::CommandNotOnPath => [
+                "Command '{command}' was not found on PATH",
+                "在 PATH 中找不到命令 '{command}'",
+                "PATH にコマンド '{command}' が見つかりません",
+            ],
+            Message::CommandNotString => [
+                "Command must be a string",
+                "命令必须是字符串",
+                "コマンドは文字列である必要があります",
+            ],
+            Message::CommandOrUrlMissing => [
+                "Server must have either a 'command' or a 'url'",
+                "server 必须包含 'command' 或 'url'",
+                "server には 'command' か 'url' のどちらかが必要です",
+            ],
+            Message::ArgPathNotFound => [
+                "Path '{path}' does not exist",
+                "路径 '{path}' 不存在",
+                "パス '{path}' が存在しません",
+            ],
+            Message::ArgNotString => [
+                "Argument must be a string",
+                "参数必须是字符串",
+                "引数は文字列である必要があります",
+            ],
+            Message::ArgsNotStringArray => [
+                "'args' must be an array of strings",
+                "'args' 必须是字符串数组",
+                "'args' は文字列の配列である必要があります",
+            ],
+            Message::CwdNotFound => [
+                "Working directory '{path}' does not exist",
+                "工作目录 '{path}' 不存在",
+                "作業ディレクトリ '{path}' が存在しません",
+            ],
+            Message::CwdNotString => [
+                "'cwd' must be a string",
+                "'cwd' 必须是字符串",
+                "'cwd' は文字列である必要があります",
+            ],
+            Message::UrlSchemeUnsupported => [
+                "Unsupported URL scheme '{scheme}'",
+                "不支持的 URL 协议 '{scheme}'",
+                "サポートされていない URL スキーム '{scheme}' です",
+            ],
+            Message::UrlInvalid => [
+                "Invalid URL: {error}",
+                "无效的 URL：{error}",
+                "無効な URL です: {error}",
+            ],
+            Message::UrlNotString => [
+                "'url' must be a string",
+                "'url' 必须是字符串",
+                "'url' は文字列である必要があります",
+            ],
+            Message::RemoteTypeInvalid => [
+                "Remote server 'type' must be \"sse\" or \"http\"",
+                "远程 server 的 'type' 必须是 \"sse\" 或 \"http\"",
+                "リモート server の 'type' は \"sse\" か \"http\" である必要があります",
+            ],
+            Message::ServerEntryNotObject => [
+                "Server entry must be an object",
+                "server 配置必须是对象",
+                "server の設定はオブジェクトである必要があります",
+            ],
+            Message::ServerEntryInvalid => [
+                "Invalid server entry: {error}",
+                "无效的 server 配置：{error}",
+                "無効な server の設定です: {error}",
+            ],
+            Message::ServerNameCaseConflict => [
+                "Server name '{name}' only differs by case from '{other}'",
+                "server 名称 '{name}' 与 '{other}' 仅大小写不同",
+                "server 名 '{name}' は '{other}' と大文字小文字しか違いません",
+            ],
+            Message::ConfigNotObject => [
+                "Config must be a JSON object",
+                "配置必须是 JSON 对象",
+                "設定は JSON オブジェクトである必要があります",
+            ],
+            Message::ServersNotObject => [
+                "'{key}' must be an object",
+                "'{key}' 必须是对象",
+                "'{key}' はオブジェクトである必要があります",
+            ],
+            Message::FieldNotArray => [
+                "'{key}' must be an array",
+                "'{key}' 必须是数组",
+                "'{key}' は配列である必要があります",
+            ],
+            Message::JsonSyntaxError => [
+                "Invalid JSON at line {line} column {column}: {error}",
+                "第 {line} 行第 {column} 列的 JSON 无效：{error}",
+                "{line} 行 {column} 列の JSON が無効です: {error}",
+            ],
+            Message::ProfileNotFound => [
+                "Profile '{profile}' not found",
+                "找不到配置方案 '{profile}'",
+                "プロファイル '{profile}' が見つかりません",
+            ],
+            Message::ProfileServersNotInstalled => [
+                "Profile '{profile}' references servers that are not installed: {servers}",
+                "配置方案 '{profile}' 引用了未安装的 server：{servers}",
+                "プロファイル '{profile}' がインストールされていない server を参照しています: {servers}",
+            ],
+            Message::ProfileNameEmpty => [
+                "Profile name must not be empty",
+                "配置方案名称不能为空",
+                "プロファイル名を空にすることはできません",
+            ],
+            Message::SecretNotFound => [
+                "Secret '{secret}' not found",
+                "找不到密钥 '{secret}'",
+                "シークレット '{secret}' が見つかりません",
+            ],
+            Message::SecretAlreadyExists => [
+                "Secret '{secret}' already exists",
+                "密钥 '{secret}' 已存在",
+                "シークレット '{secret}' はすでに存在します",
+            ],
+            Message::SecretNameInvalid => [
+                "Invalid secret name '{secret}': use letters, digits, '_', '-' or '.'",
+                "无效的密钥名 '{secret}'：只能使用字母、数字、'_'、'-' 或 '.'",
+                "無効なシークレット名 '{secret}' です: 英数字、'_'、'-'、'.' を使ってください",
+            ],
+            Message::SecretInUse => [
+                "Secret '{secret}' is still used by: {servers}",
+                "密钥 '{secret}' 仍被使用：{servers}",
+                "シークレット '{secret}' はまだ使われています: {servers}",
+            ],
+            Message::SecretCorrupted => [
+                "Secret '{secret}' is corrupted",
+                "密钥 '{secret}' 已损坏",
+                "シークレット '{secret}' が壊れています",
+            ],
+            Message::SecretValueNotSet => [
+                "'{key}' is not set for MCP server '{server}'",
+                "MCP server '{server}' 中没有设置 '{key}'",
+                "MCP server '{server}' に '{key}' が設定されていません",
+            ],
+            Message::SecretValueIsReference => [
+                "'{key}' already references a secret",
+                "'{key}' 已经引用了密钥",
+                "'{key}' はすでにシークレットを参照しています",
+            ],
+            Message::VaultKeyMismatch => [
+                "Failed to decrypt secret '{secret}': the vault key does not match",
+                "解密密钥 '{secret}' 失败：密钥文件不匹配",
+                "シークレット '{secret}' を復号できません: 鍵ファイルが一致しません",
+            ],
+            Message::AppDataDirFailed => [
+                "Failed to get app data directory: {error}",
+                "获取应用数据目录失败：{error}",
+                "アプリのデータディレクトリを取得できませんでした: {error}",
+            ],
+            Message::VaultKeyInvalid => [
+                "Invalid vault key file: {path}",
+                "无效的密钥文件：{path}",
+                "無効な鍵ファイルです: {path}",
+            ],
+            Message::VaultKeyReadFailed => [
+                "Failed to read vault key: {error}",
+                "读取密钥文件失败：{error}",
+                "鍵ファイルの読み込みに失敗しました: {error}",
+            ],
+            Message::VaultKeyWriteFailed => [
+                "Failed to write vault key: {error}",
+                "写入密钥文件失败：{error}",
+                "鍵ファイルの書き込みに失敗しました: {error}",
+            ],
+            Message::VariablesUnresolved => [
+                "Unresolved variables: {variables}",
+                "无法解析的变量：{variables}",
+                "解決できない変数があります: {variables}",
+            ],
+            Message::VariableBuiltIn => [
+                "'{variable}' is a built-in variable",
+                "'{variable}' 是内置变量",
+                "'{variable}' は組み込み変数です",
+            ],
+            Message::VariableNameInvalid => [
+                "Invalid variable name '{variable}': use letters, digits, '_', '-' or '.'",
+                "无效的变量名 '{variable}'：只能使用字母、数字、'_'、'-' 或 '.'",
+                "無効な変数名 '{variable}' です: 英数字、'_'、'-'、'.' を使ってください",
+            ],
+            Message::VariableNotFound => [
+                "Variable '{variable}' not found",
+                "找不到变量 '{variable}'",
+                "変数 '{variable}' が見つかりません",
+            ],
+            Message::ServersAlreadyInstalled => [
+                "MCP servers already installed: {servers}",
+                "MCP server 已安装：{servers}",
+                "MCP server はすでにインストールされています: {servers}",
+            ],
+            Message::PlaceholdersMissing => [
+                "Missing values for placeholders: {placeholders}",
+                "缺少占位符的值：{placeholders}",
+                "プレースホルダーの値がありません: {placeholders}",
+            ],
+            Message::PlaceholderMissing => [
+                "Missing value for placeholder '{placeholder}'",
+                "缺少占位符 '{placeholder}' 的值",
+                "プレースホルダー '{placeholder}' の値がありません",
+            ],
+            Message::BundleReadFailed => [
+                "Failed to read bundle file: {error}",
+                "读取导出文件失败：{error}",
+                "エクスポートファイルの読み込みに失敗しました: {error}",
+            ],
+            Message::BundleWriteFailed => [
+                "Failed to write bundle file: {error}",
+                "写入导出文件失败：{error}",
+                "エクスポートファイルの書き込みに失敗しました: {error}",
+            ],
+            Message::BundleParseFailed => [
+                "Failed to parse bundle: {error}",
+                "解析导出文件失败：{error}",
+                "エクスポートファイルの解析に失敗しました: {error}",
+            ],
+            Message::BundleVersionUnsupported => [
+                "Bundle version {version} is newer than the supported version {supported}",
+                "导出文件版本 {version} 高于支持的版本 {supported}",
+                "エクスポートファイルのバージョン {version} はサポートされているバージョン {supported} より新しいです",
+            ],
+            Message::StoreOpenFailed => [
+                "Failed to create store: {error}",
+                "打开存储失败：{error}",
+                "ストアを開けませんでした: {error}",
+            ],
+            Message::StoreReadFailed => [
+                "Failed to read '{key}' from store: {error}",
+                "读取存储中的 '{key}' 失败：{error}",
+                "ストアの '{key}' の読み込みに失敗しました: {error}",
+            ],
+            Message::StoreSaveFailed => [
+                "Failed to save store: {error}",
+                "保存存储失败：{error}",
+                "ストアの保存に失敗しました: {error}",
+            ],
+        }
+    }
+
+    pub fn text(self, language: Language) -> &'static str {
+        let [en, zh, ja] = self.texts();
+        match language {
+            Language::En => en,
+            Language::Zh => zh,
+            Language::Ja => ja,
+        }
+    }
+}
+
+// 当前语言下的文本
+pub fn t(message: Message) -> &'static str {
+    message.text(current_language())
+}
+
+// 当前语言下的文本，并替换 {name} 占位符
+pub fn t_args(message: Message, args: &[(&str, &str)]) -> String {
+    let mut text = t(message).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageSettings {
+    // 实际使用的语言
+    pub language: Language,
+    // 用户选择的语言，None 表示跟随系统
+    pub preference: Option<Language>,
+}
+
+pub fn init_language<R: Runtime>(app: &AppHandle<R>) {
+    match crate::store::load_language(app) {
+        Ok(Some(language)) => set_current_language(language),
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to load language: {}", e),
+    }
+}
+
+#[tauri::command]
+pub fn get_language(app: AppHandle) -> Result<LanguageSettings, AppError> {
+    Ok(LanguageSettings {
+        language: current_language(),
+        preference: crate::store::load_language(&app)?,
+    })
+}
+
+#[tauri::command]
+pub fn set_language(
+    app: AppHandle,
+    language: Option<Language>,
+) -> Result<LanguageSettings, AppError> {
+    crate::store::save_language(&app, language)?;
+    set_current_language(language.unwrap_or_else(system_language));
+
+    // 托盘菜单需要重新生成
+    let _ = crate::tray::update_tray_status(app.clone());
+    get_language(app)
+}
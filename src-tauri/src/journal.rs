@@ -0,0 +1,733 @@
+use crate::claude_config::with_config_lock;
+use crate::config_backup::backup_config;
+use crate::config_diff::{apply_changes, unescape_pointer_token, ConfigChange};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::{t_args, Message};
+use crate::mcp_clients::{
+    read_client_config, write_client_config, ClientConfig, ConfigPreview, McpClient,
+};
+use crate::secrets::walk_strings;
+use crate::store::{self, DisabledMcpServer, InstalledMcpServer};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Runtime};
+
+// 最多保留的操作记录数
+const MAX_JOURNAL_ENTRIES: usize = 100;
+
+// 串行化记录、撤销和重做
+static JOURNAL_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// 某个客户端配置文件的变化；由密钥或环境变量引用展开的 env 和 headers 的值
+// 整体记录为引用，references 为这些值的位置，撤销或重做时再展开
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfigChange {
+    pub client: McpClient,
+    pub changes: Vec<ConfigChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<JournalReference>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSide {
+    Before,
+    After,
+}
+
+// 记录为引用的值，pointer 为它在配置文件中的位置，side 为变化前或变化后的值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalReference {
+    pub pointer: String,
+    pub side: ChangeSide,
+    pub reference: String,
+}
+
+// store 中已安装 server 记录的变化，None 表示不存在
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalServerChange {
+    pub name: String,
+    pub before: Option<InstalledMcpServer>,
+    pub after: Option<InstalledMcpServer>,
+}
+
+impl JournalServerChange {
+    pub fn new(
+        name: &str,
+        before: Option<&InstalledMcpServer>,
+        after: Option<&InstalledMcpServer>,
+    ) -> Self {
+        JournalServerChange {
+            name: name.to_string(),
+            before: before.cloned(),
+            after: after.cloned(),
+        }
+    }
+}
+
+// store 中某个值的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalValueChange<T> {
+    pub before: T,
+    pub after: T,
+}
+
+// 一次操作中 store 的变化，None 表示没有变化
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JournalStoreChanges {
+    #[serde(default)]
+    pub servers: Vec<JournalServerChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<JournalValueChange<Vec<DisabledMcpServer>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<JournalValueChange<Option<String>>>,
+}
+
+impl JournalStoreChanges {
+    pub fn servers(servers: Vec<JournalServerChange>) -> Self {
+        JournalStoreChanges {
+            servers,
+            ..Default::default()
+        }
+    }
+
+    // 禁用列表有变化时记录
+    pub fn with_disabled(
+        mut self,
+        before: Vec<DisabledMcpServer>,
+        after: &[DisabledMcpServer],
+    ) -> Self {
+        let changed = serde_json::to_value(&before).ok() != serde_json::to_value(after).ok();
+        if changed {
+            self.disabled = Some(JournalValueChange {
+                before,
+                after: after.to_vec(),
+            });
+        }
+        self
+    }
+
+    pub fn with_active_profile(mut self, before: Option<String>, after: Option<&str>) -> Self {
+        if before.as_deref() != after {
+            self.active_profile = Some(JournalValueChange {
+                before,
+                after: after.map(|name| name.to_string()),
+            });
+        }
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.servers.is_empty() && self.disabled.is_none() && self.active_profile.is_none()
+    }
+}
+
+// 一次操作，撤销时反向应用 configs 和 store 的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub description: String,
+    pub timestamp: String,
+    pub configs: Vec<JournalConfigChange>,
+    #[serde(flatten)]
+    pub store: JournalStoreChanges,
+}
+
+// position 之前的记录已生效，可以撤销；之后的可以重做
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+    pub position: usize,
+}
+
+// 记录一次已完成的修改，失败时只打印日志，不影响修改本身
+pub fn record<R: Runtime>(
+    app: &AppHandle<R>,
+    description: String,
+    previews: &[ConfigPreview],
+    store: JournalStoreChanges,
+) {
+    let configs = previews
+        .iter()
+        .map(|preview| JournalConfigChange {
+            client: preview.client,
+            changes: preview.changes.clone(),
+            references: Vec::new(),
+        })
+        .collect();
+    record_changes(app, description, configs, store);
+}
+
+pub fn record_changes<R: Runtime>(
+    app: &AppHandle<R>,
+    description: String,
+    configs: Vec<JournalConfigChange>,
+    store: JournalStoreChanges,
+) {
+    let mut configs: Vec<JournalConfigChange> = configs
+        .into_iter()
+        .filter(|config| !config.changes.is_empty())
+        .collect();
+    if configs.is_empty() && store.is_empty() {
+        return;
+    }
+
+    let result = redact_configs(app, &store, &mut configs).and_then(|_| {
+        let now = chrono::Local::now();
+        let entry = JournalEntry {
+            id: now.format("%Y%m%d-%H%M%S-%3f").to_string(),
+            description,
+            timestamp: now.to_rfc3339(),
+            configs,
+            store,
+        };
+
+        let _guard = lock_journal()?;
+        let mut journal = store::load_journal(app)?;
+        // 新的操作使之后的重做记录失效
+        journal.entries.truncate(journal.position);
+        journal.entries.push(entry);
+        if journal.entries.len() > MAX_JOURNAL_ENTRIES {
+            let excess = journal.entries.len() - MAX_JOURNAL_ENTRIES;
+            journal.entries.drain(..excess);
+        }
+        journal.position = journal.entries.len();
+        store::save_journal(app, &journal)
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to record journal entry: {}", e);
+    }
+}
+
+fn lock_journal() -> Result<MutexGuard<'static, ()>, AppError> {
+    JOURNAL_LOCK
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock journal: {}", e)))
+}
+
+fn side_value<T>(side: ChangeSide, before: T, after: T) -> T {
+    match side {
+        ChangeSide::Before => before,
+        ChangeSide::After => after,
+    }
+}
+
+// 把所有配置变化中由引用展开的值替换为引用
+fn redact_configs<R: Runtime>(
+    app: &AppHandle<R>,
+    store: &JournalStoreChanges,
+    configs: &mut [JournalConfigChange],
+) -> Result<(), AppError> {
+    let mut templates: HashMap<(String, ChangeSide), Option<JsonValue>> = HashMap::new();
+    for config in configs {
+        let servers_key = config.client.servers_key();
+        for change in &mut config.changes {
+            for side in [ChangeSide::Before, ChangeSide::After] {
+                let mut positions = Vec::new();
+                redact_change(servers_key, change, side, &mut positions, &mut |server| {
+                    let key = (server.to_string(), side);
+                    if !templates.contains_key(&key) {
+                        let template = server_template(app, store, server, side)?;
+                        templates.insert(key.clone(), template);
+                    }
+                    Ok(templates[&key].clone())
+                })?;
+                config.references.extend(positions);
+            }
+        }
+    }
+    Ok(())
+}
+
+// server 在变化前或变化后的配置，已应用当时的 profile；不由本程序管理的 server 为 None
+fn server_template<R: Runtime>(
+    app: &AppHandle<R>,
+    store: &JournalStoreChanges,
+    server: &str,
+    side: ChangeSide,
+) -> Result<Option<JsonValue>, AppError> {
+    // 记录时 store 已经是变化后的状态
+    let installed = match store.servers.iter().find(|change| change.name == server) {
+        Some(change) => side_value(side, &change.before, &change.after).clone(),
+        None => store::load_installed_servers(app)?.remove(server),
+    };
+    let Some(installed) = installed else {
+        return Ok(None);
+    };
+    let profile = match &store.active_profile {
+        Some(change) => side_value(side, &change.before, &change.after).clone(),
+        None => store::load_active_profile(app)?,
+    };
+    let entry = crate::profiles::profile_entry(app, profile.as_deref(), server, &installed.entry)?;
+    serde_json::to_value(entry)
+        .map(Some)
+        .map_err(|e| AppError::internal(format!("Failed to serialize server: {}", e)))
+}
+
+// 配置文件中某个 server 的 env 或 headers 里的一项，返回 (server, 字段, 键)
+fn reference_position(servers_key: &str, pointer: &str) -> Option<(String, String, String)> {
+    let tokens: Vec<String> = pointer
+        .split('/')
+        .skip(1)
+        .map(unescape_pointer_token)
+        .collect();
+    let [first, server, .., field, key] = tokens.as_slice() else {
+        return None;
+    };
+    if first != servers_key || (field != "env" && field != "headers") {
+        return None;
+    }
+    Some((server.clone(), field.clone(), key.clone()))
+}
+
+// template 中 env 或 headers 的某一项含有密钥或环境变量引用时返回该项
+fn template_reference<'a>(template: &'a JsonValue, field: &str, key: &str) -> Option<&'a str> {
+    let text = template.get(field)?.get(key)?.as_str()?;
+    (text.contains("${secret:") || text.contains("${env:")).then_some(text)
+}
+
+// 把 change 某一侧中由引用展开的 env 和 headers 的值整体替换为引用，位置记入 positions。
+// template 返回 server 在这一侧的配置
+fn redact_change(
+    servers_key: &str,
+    change: &mut ConfigChange,
+    side: ChangeSide,
+    positions: &mut Vec<JournalReference>,
+    template: &mut impl FnMut(&str) -> Result<Option<JsonValue>, AppError>,
+) -> Result<(), AppError> {
+    let path = change.path.clone();
+    let Some(value) = side_value(side, change.before.as_mut(), change.after.as_mut()) else {
+        return Ok(());
+    };
+
+    let mut candidates = Vec::new();
+    walk_strings(value, path.clone(), &mut |pointer, text| {
+        if let Some(position) = reference_position(servers_key, pointer) {
+            candidates.push((pointer.to_string(), text.to_string(), position));
+        }
+    });
+
+    for (pointer, text, (server, field, key)) in candidates {
+        let Some(template) = template(&server)? else {
+            continue;
+        };
+        let Some(reference) = template_reference(&template, &field, &key) else {
+            continue;
+        };
+        if reference == text {
+            continue;
+        }
+        if let Some(slot) = value.pointer_mut(&pointer[path.len()..]) {
+            *slot = JsonValue::String(reference.to_string());
+            positions.push(JournalReference {
+                pointer,
+                side,
+                reference: reference.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+// 展开记录中的引用：要写入的一侧使用引用当前的值；要核对的一侧没有记录明文，
+// 使用配置文件中的当前值，这样密钥轮换后仍然可以撤销
+fn reveal_references(
+    changes: &mut [ConfigChange],
+    references: &[JournalReference],
+    document: &JsonValue,
+    reverse: bool,
+    resolve: &mut impl FnMut(&JournalReference) -> Result<String, AppError>,
+) -> Result<(), AppError> {
+    let target = if reverse {
+        ChangeSide::Before
+    } else {
+        ChangeSide::After
+    };
+
+    for reference in references {
+        let Some(change) = changes.iter_mut().find(|change| {
+            reference.pointer == change.path
+                || reference.pointer.starts_with(&format!("{}/", change.path))
+        }) else {
+            continue;
+        };
+        let value = if reference.side == target {
+            JsonValue::String(resolve(reference)?)
+        } else {
+            match document.pointer(&reference.pointer) {
+                Some(current) => current.clone(),
+                None => continue,
+            }
+        };
+        let inner = &reference.pointer[change.path.len()..];
+        if let Some(slot) = side_value(
+            reference.side,
+            change.before.as_mut(),
+            change.after.as_mut(),
+        )
+        .and_then(|side| side.pointer_mut(inner))
+        {
+            *slot = value;
+        }
+    }
+    Ok(())
+}
+
+// 按生成客户端配置时的规则展开引用：env 中的变量和所有密钥引用
+fn resolve_reference<R: Runtime>(
+    app: &AppHandle<R>,
+    reference: &JournalReference,
+) -> Result<String, AppError> {
+    let field = reference.pointer.rsplit('/').nth(1).unwrap_or("env");
+    let mut value = serde_json::json!({ field: { "value": reference.reference } });
+    crate::variables::resolve_value(app, &mut value)?;
+    Ok(value[field]["value"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+// store 中会被一条记录改动的值，应用失败时恢复
+struct StoreSnapshot {
+    servers: Option<HashMap<String, InstalledMcpServer>>,
+    disabled: Option<Vec<DisabledMcpServer>>,
+    active_profile: Option<Option<String>>,
+}
+
+impl StoreSnapshot {
+    fn take<R: Runtime>(
+        app: &AppHandle<R>,
+        changes: &JournalStoreChanges,
+    ) -> Result<Self, AppError> {
+        Ok(StoreSnapshot {
+            servers: if changes.servers.is_empty() {
+                None
+            } else {
+                Some(store::load_installed_servers(app)?)
+            },
+            disabled: match changes.disabled {
+                Some(_) => Some(store::load_disabled_servers(app)?),
+                None => None,
+            },
+            active_profile: match changes.active_profile {
+                Some(_) => Some(store::load_active_profile(app)?),
+                None => None,
+            },
+        })
+    }
+
+    // 尽量恢复，失败时只打印日志
+    fn restore<R: Runtime>(self, app: &AppHandle<R>) {
+        if let Some(servers) = self.servers {
+            if let Err(e) = store::update_installed_servers(app, |installed| {
+                *installed = servers;
+                Ok(())
+            }) {
+                eprintln!("Failed to restore installed servers: {}", e);
+            }
+        }
+        if let Some(disabled) = self.disabled {
+            if let Err(e) = store::save_disabled_servers(app, &disabled) {
+                eprintln!("Failed to restore disabled servers: {}", e);
+            }
+        }
+        if let Some(active_profile) = self.active_profile {
+            if let Err(e) = store::save_active_profile(app, active_profile.as_deref()) {
+                eprintln!("Failed to restore active profile: {}", e);
+            }
+        }
+    }
+}
+
+fn apply_store<R: Runtime>(
+    app: &AppHandle<R>,
+    changes: &JournalStoreChanges,
+    reverse: bool,
+) -> Result<(), AppError> {
+    let target = if reverse {
+        ChangeSide::Before
+    } else {
+        ChangeSide::After
+    };
+
+    if !changes.servers.is_empty() {
+        store::update_installed_servers(app, |installed| {
+            for server in &changes.servers {
+                match side_value(target, &server.before, &server.after) {
+                    Some(value) => installed.insert(server.name.clone(), value.clone()),
+                    None => installed.remove(&server.name),
+                };
+            }
+            Ok(())
+        })?;
+    }
+
+    if let Some(disabled) = &changes.disabled {
+        store::save_disabled_servers(app, side_value(target, &disabled.before, &disabled.after))?;
+    }
+
+    if let Some(active_profile) = &changes.active_profile {
+        let value = side_value(target, &active_profile.before, &active_profile.after);
+        store::save_active_profile(app, value.as_deref())?;
+    }
+
+    Ok(())
+}
+
+// 把配置文件改回 original 的内容
+fn restore_config(original: &ClientConfig) -> Result<(), AppError> {
+    let mut config = read_client_config(original.client)?;
+    config.document = original.document.clone();
+    write_client_config(&config)
+}
+
+// 应用（或反向应用）一条记录：先在内存中检查所有客户端，再更新 store，最后写入配置文件；
+// 任何一步失败都恢复已经做出的修改
+fn apply_entry<R: Runtime>(
+    app: &AppHandle<R>,
+    entry: &JournalEntry,
+    reverse: bool,
+) -> Result<(), AppError> {
+    with_config_lock(|| {
+        let mut configs = Vec::new();
+        for change in &entry.configs {
+            let original = read_client_config(change.client)?;
+            let mut document = JsonValue::Object(original.document.clone());
+            let mut changes = change.changes.clone();
+            reveal_references(
+                &mut changes,
+                &change.references,
+                &document,
+                reverse,
+                &mut |reference| resolve_reference(app, reference),
+            )?;
+
+            apply_changes(&mut document, &changes, reverse).map_err(|e| AppError {
+                message: t_args(
+                    Message::JournalApplyFailed,
+                    &[
+                        ("description", &entry.description),
+                        ("client", change.client.display_name()),
+                        ("error", &e.message),
+                    ],
+                ),
+                ..e.with("client", change.client.id())
+            })?;
+            let JsonValue::Object(document) = document else {
+                return Err(AppError::localized(
+                    ErrorCode::ConfigInvalid,
+                    Message::ConfigNotObject,
+                    &[],
+                ));
+            };
+            let mut config = original.clone();
+            config.document = document;
+            configs.push((original, config));
+        }
+
+        let snapshot = StoreSnapshot::take(app, &entry.store)?;
+        if let Err(e) = apply_store(app, &entry.store, reverse) {
+            snapshot.restore(app);
+            return Err(e);
+        }
+
+        for (index, (_, config)) in configs.iter().enumerate() {
+            let result = backup_config(config.client).and_then(|_| write_client_config(config));
+            if let Err(e) = result {
+                for (original, _) in &configs[..index] {
+                    if let Err(e) = restore_config(original) {
+                        eprintln!(
+                            "Failed to restore {} config: {}",
+                            original.client.display_name(),
+                            e
+                        );
+                    }
+                }
+                snapshot.restore(app);
+                return Err(e);
+            }
+        }
+        Ok(())
+    })?;
+
+    if entry.store.active_profile.is_some() {
+        let _ = crate::tray::update_tray_status(app.clone());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_config_journal(app: AppHandle) -> Result<Journal, AppError> {
+    store::load_journal(&app)
+}
+
+// 撤销最近一次操作，没有可撤销的操作时返回 None
+#[tauri::command]
+pub fn undo_config_change(app: AppHandle) -> Result<Option<JournalEntry>, AppError> {
+    let _guard = lock_journal()?;
+    let mut journal = store::load_journal(&app)?;
+    let Some(position) = journal.position.checked_sub(1) else {
+        return Ok(None);
+    };
+    let Some(entry) = journal.entries.get(position).cloned() else {
+        return Ok(None);
+    };
+
+    apply_entry(&app, &entry, true)?;
+
+    journal.position = position;
+    store::save_journal(&app, &journal)?;
+    Ok(Some(entry))
+}
+
+#[tauri::command]
+pub fn redo_config_change(app: AppHandle) -> Result<Option<JournalEntry>, AppError> {
+    let _guard = lock_journal()?;
+    let mut journal = store::load_journal(&app)?;
+    let Some(entry) = journal.entries.get(journal.position).cloned() else {
+        return Ok(None);
+    };
+
+    apply_entry(&app, &entry, false)?;
+
+    journal.position += 1;
+    store::save_journal(&app, &journal)?;
+    Ok(Some(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_diff::{diff_json, ChangeKind};
+    use serde_json::json;
+
+    fn template() -> JsonValue {
+        json!({ "command": "node", "env": { "TOKEN": "${secret:token}", "MODE": "dev" } })
+    }
+
+    fn install_change(token: &str) -> ConfigChange {
+        ConfigChange {
+            kind: ChangeKind::Added,
+            path: "/mcpServers/api".to_string(),
+            before: None,
+            after: Some(json!({
+                "command": "node",
+                "args": ["--token", token],
+                "env": { "TOKEN": token, "MODE": "dev" }
+            })),
+        }
+    }
+
+    fn redact(change: &mut ConfigChange, side: ChangeSide) -> Vec<JournalReference> {
+        let mut positions = Vec::new();
+        redact_change("mcpServers", change, side, &mut positions, &mut |server| {
+            Ok((server == "api").then(template))
+        })
+        .unwrap();
+        positions
+    }
+
+    #[test]
+    fn finds_env_and_header_positions() {
+        assert_eq!(
+            reference_position("servers", "/servers/a~1b/env/TOKEN"),
+            Some(("a/b".to_string(), "env".to_string(), "TOKEN".to_string()))
+        );
+        assert_eq!(
+            reference_position("context_servers", "/context_servers/x/command/env/K"),
+            Some(("x".to_string(), "env".to_string(), "K".to_string()))
+        );
+        assert_eq!(
+            reference_position("mcpServers", "/mcpServers/x/args/0"),
+            None
+        );
+        assert_eq!(reference_position("mcpServers", "/servers/x/env/K"), None);
+    }
+
+    #[test]
+    fn redacts_only_whole_values_from_references() {
+        let mut change = install_change("abc");
+        let positions = redact(&mut change, ChangeSide::After);
+
+        let after = change.after.unwrap();
+        assert_eq!(after["env"]["TOKEN"], "${secret:token}");
+        // 同样的字符串出现在其它位置时不改动
+        assert_eq!(after["args"][1], "abc");
+        assert_eq!(after["env"]["MODE"], "dev");
+        assert_eq!(
+            positions,
+            vec![JournalReference {
+                pointer: "/mcpServers/api/env/TOKEN".to_string(),
+                side: ChangeSide::After,
+                reference: "${secret:token}".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_servers_without_references() {
+        let mut change = install_change("abc");
+        change.path = "/mcpServers/other".to_string();
+        assert!(redact(&mut change, ChangeSide::After).is_empty());
+        assert_eq!(change.after.unwrap()["env"]["TOKEN"], "abc");
+    }
+
+    #[test]
+    fn undo_after_rotation_uses_current_value() {
+        let mut change = install_change("old");
+        let references = redact(&mut change, ChangeSide::After);
+
+        // 密钥轮换后配置文件中已是新的值
+        let mut document = json!({ "mcpServers": {} });
+        apply_changes(&mut document, &[install_change("new")], false).unwrap();
+
+        let mut changes = vec![change];
+        reveal_references(&mut changes, &references, &document, true, &mut |_| {
+            panic!("undo does not write the removed value")
+        })
+        .unwrap();
+        // args 中的值不是由引用展开的，只能按原样核对
+        changes[0].after.as_mut().unwrap()["args"][1] = json!("new");
+        apply_changes(&mut document, &changes, true).unwrap();
+        assert_eq!(document, json!({ "mcpServers": {} }));
+    }
+
+    #[test]
+    fn redo_expands_references() {
+        let mut change = install_change("abc");
+        let references = redact(&mut change, ChangeSide::After);
+
+        let mut changes = vec![change];
+        let mut document = json!({ "mcpServers": {} });
+        reveal_references(
+            &mut changes,
+            &references,
+            &document,
+            false,
+            &mut |reference| {
+                assert_eq!(reference.reference, "${secret:token}");
+                Ok("rotated".to_string())
+            },
+        )
+        .unwrap();
+        apply_changes(&mut document, &changes, false).unwrap();
+        assert_eq!(document["mcpServers"]["api"]["env"]["TOKEN"], "rotated");
+    }
+
+    #[test]
+    fn redacts_modified_values_on_both_sides() {
+        let before = json!({ "mcpServers": { "api": { "env": { "TOKEN": "v1" } } } });
+        let after = json!({ "mcpServers": { "api": { "env": { "TOKEN": "v2" } } } });
+        let mut changes = diff_json(&before, &after);
+        assert_eq!(changes.len(), 1);
+
+        let mut references = redact(&mut changes[0], ChangeSide::Before);
+        references.extend(redact(&mut changes[0], ChangeSide::After));
+        assert_eq!(changes[0].before, Some(json!("${secret:token}")));
+        assert_eq!(changes[0].after, Some(json!("${secret:token}")));
+        assert_eq!(references.len(), 2);
+    }
+}
@@ -1,21 +1,44 @@
 mod claude_config;
+mod config_backup;
+mod config_diff;
+mod config_validation;
+mod config_watcher;
 mod env_check;
+mod error;
+mod i18n;
+mod journal;
+mod log_files;
+mod mcp_clients;
+mod mcp_inspector;
 mod mcp_runner;
 mod mcp_servers;
+mod mcp_session;
+mod profiles;
+mod secrets;
+mod server_bundle;
+mod server_entry;
+mod server_logs;
 mod store;
+mod supervisor;
 mod tray;
+mod variables;
 
 use claude_config::{
-    get_claude_config, get_config_path, restore_config_backup, save_claude_config,
+    get_claude_config, get_claude_config_status, get_config_path, save_claude_config,
     update_global_shortcut_command,
 };
+use config_backup::{
+    diff_config_backup, get_backup_retention, list_config_backups, restore_config_backup_by_id,
+    set_backup_retention,
+};
 use env_check::{
     check_claude_installed, get_node_path, get_python_path, get_uv_path, install_environment,
 };
 use mcp_runner::{get_server_status, restart_claude_app, start_server, stop_server};
 use mcp_servers::{
-    get_mcp_server_templates, install_mcp_server, is_mcp_server_installed, select_folder,
-    uninstall_mcp_server, update_mcp_server_config,
+    disable_mcp_server, enable_mcp_server, get_mcp_server_templates, install_mcp_server,
+    is_mcp_server_installed, list_disabled_servers, select_folder, uninstall_mcp_server,
+    update_mcp_server_config,
 };
 use std::time::Duration;
 use tauri::Runtime;
@@ -39,11 +62,35 @@ pub fn setup_app<R: Runtime>(app: &tauri::App<R>) -> Result<(), Box<dyn std::err
         }
     });
 
+    // 在创建托盘之前确定界面语言
+    i18n::init_language(app_handle);
+
+    // 读取用户指定的配置文件路径，托盘和配置监听都依赖它
+    claude_config::init_config_path(app_handle);
+
+    // 读取工作区目录，VS Code 工作区配置依赖它
+    variables::init_workspace_dir(app_handle);
+
+    // 配置快照保存在 app 的数据目录中
+    config_backup::init_backup_dir(app_handle);
+
+    // server 日志写入 app 的日志目录，并清理过期的文件
+    log_files::init_log_dir(app_handle);
+
+    // 读取备份保留数量设置
+    config_backup::init_backup_retention(app_handle);
+
+    // 检查本地 server 是否退出，并按重启策略处理
+    supervisor::start_supervisor(app_handle.clone());
+
     // 创建系统托盘
-    if let Err(e) = tray::create_tray(&app_handle) {
+    if let Err(e) = tray::create_tray(app_handle) {
         eprintln!("Failed to create tray: {}", e);
     }
 
+    // 监听配置文件变化，替代定时轮询；失败时在后台重试
+    config_watcher::ensure_config_watcher(app_handle.clone());
+
     Ok(())
 }
 
@@ -63,17 +110,82 @@ pub fn run() {
             get_node_path,
             get_uv_path,
             get_claude_config,
+            get_claude_config_status,
+            claude_config::get_config_path_info,
+            claude_config::set_config_path,
+            config_watcher::get_config_watcher_status,
             get_config_path,
             check_claude_installed,
             get_mcp_server_templates,
             install_mcp_server,
             uninstall_mcp_server,
+            disable_mcp_server,
+            enable_mcp_server,
+            list_disabled_servers,
             is_mcp_server_installed,
-            restore_config_backup,
+            list_config_backups,
+            diff_config_backup,
+            restore_config_backup_by_id,
+            journal::get_config_journal,
+            journal::undo_config_change,
+            journal::redo_config_change,
+            i18n::get_language,
+            i18n::set_language,
+            get_backup_retention,
+            set_backup_retention,
+            config_validation::validate_claude_config,
+            mcp_clients::list_mcp_clients,
+            mcp_clients::list_mcp_servers,
+            mcp_clients::get_server_clients,
+            profiles::list_profiles,
+            profiles::save_profile,
+            profiles::delete_profile,
+            profiles::get_active_profile,
+            profiles::switch_profile,
+            secrets::list_secrets,
+            secrets::set_secret,
+            secrets::rotate_secret,
+            secrets::delete_secret,
+            secrets::get_secret_usages,
+            secrets::move_value_to_secret,
+            secrets::rotate_vault_key,
+            variables::list_variables,
+            variables::set_variable,
+            variables::delete_variable,
+            variables::get_workspace_dir,
+            variables::set_workspace_dir,
+            server_bundle::export_server_bundle,
+            server_bundle::preview_server_bundle,
+            server_bundle::import_server_bundle,
             save_claude_config,
             get_server_status,
             start_server,
             stop_server,
+            mcp_runner::get_server_launch_spec,
+            mcp_runner::get_env_inherit_policy,
+            mcp_runner::set_env_inherit_policy,
+            mcp_runner::get_server_details,
+            mcp_runner::get_handshake_timeout,
+            mcp_runner::set_handshake_timeout,
+            mcp_inspector::list_server_tools,
+            mcp_inspector::call_server_tool,
+            mcp_inspector::list_server_resources,
+            mcp_inspector::list_server_resource_templates,
+            mcp_inspector::read_server_resource,
+            mcp_inspector::list_server_prompts,
+            mcp_inspector::get_server_prompt,
+            supervisor::get_restart_policy,
+            supervisor::set_restart_policy,
+            supervisor::get_server_crashes,
+            supervisor::clear_server_crashes,
+            server_logs::get_server_logs,
+            server_logs::clear_server_logs,
+            server_logs::subscribe_server_logs,
+            server_logs::unsubscribe_server_logs,
+            log_files::list_server_log_files,
+            log_files::read_server_log_file,
+            log_files::export_server_logs,
+            log_files::purge_server_logs,
             select_folder,
             update_mcp_server_config,
             install_environment,
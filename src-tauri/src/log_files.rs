@@ -0,0 +1,358 @@
+use crate::error::AppError;
+use crate::i18n::Message;
+use crate::server_logs::LogLine;
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+// 当前文件超过该大小时轮转
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+// 当前文件写入超过该时间后轮转
+const MAX_FILE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+// 每个 server 保留的已轮转文件数量
+const MAX_ROTATED_FILES: usize = 5;
+// 超过该时间的已轮转文件在启动时删除
+const RETENTION: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+// 日志目录，启动时根据 app 的日志目录初始化
+static LOG_DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+// 正在写入的文件
+static OPEN_FILES: Lazy<Mutex<HashMap<String, ActiveFile>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct ActiveFile {
+    file: File,
+    size: u64,
+    // 本次打开或轮转的时间；不用文件的创建时间，有的文件系统不提供，
+    // NTFS 上同名重建的文件还会沿用旧文件的创建时间
+    opened_at: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogFileInfo {
+    pub file_name: String,
+    pub size: u64,
+    pub modified: String,
+    // 是否为正在写入的文件
+    pub current: bool,
+}
+
+// server 名称可能包含路径分隔符等字符，不能直接用作文件名；
+// 不保留 '.'，以免与轮转文件的序号混淆。替换字符后不同的名称可能相同，
+// 例如 foo.bar 和 foo_bar，因此加上原名称的哈希
+fn file_stem(server: &str) -> String {
+    let name: String = server
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let hash = format!("{:x}", Sha256::digest(server.as_bytes()));
+    format!("{}-{}", name, &hash[..8])
+}
+
+fn log_dir() -> Result<PathBuf, AppError> {
+    LOG_DIR
+        .read()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .ok_or_else(|| AppError::internal("Log directory is not initialized"))
+}
+
+fn current_file_path(dir: &Path, server: &str) -> PathBuf {
+    dir.join(format!("{}.log", file_stem(server)))
+}
+
+// index 越大越旧
+fn rotated_file_path(dir: &Path, server: &str, index: usize) -> PathBuf {
+    dir.join(format!("{}.{}.log", file_stem(server), index))
+}
+
+// 当前文件在前，之后按从新到旧排列，只包含存在的文件
+fn server_files(dir: &Path, server: &str) -> Vec<PathBuf> {
+    std::iter::once(current_file_path(dir, server))
+        .chain((1..=MAX_ROTATED_FILES).map(|index| rotated_file_path(dir, server, index)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn open_file(dir: &Path, server: &str) -> io::Result<ActiveFile> {
+    fs::create_dir_all(dir)?;
+    let path = current_file_path(dir, server);
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    Ok(ActiveFile {
+        size: file.metadata()?.len(),
+        opened_at: Instant::now(),
+        file,
+    })
+}
+
+// 依次把 name.N.log 改名为 name.N+1.log，最旧的文件被覆盖
+fn rotate(dir: &Path, server: &str) -> io::Result<()> {
+    let oldest = rotated_file_path(dir, server, MAX_ROTATED_FILES);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..MAX_ROTATED_FILES).rev() {
+        let from = rotated_file_path(dir, server, index);
+        if from.exists() {
+            fs::rename(&from, rotated_file_path(dir, server, index + 1))?;
+        }
+    }
+    let current = current_file_path(dir, server);
+    if current.exists() {
+        fs::rename(&current, rotated_file_path(dir, server, 1))?;
+    }
+    Ok(())
+}
+
+fn needs_rotation(active: &ActiveFile) -> bool {
+    active.size >= MAX_FILE_SIZE || active.opened_at.elapsed() >= MAX_FILE_AGE
+}
+
+fn write_line(dir: &Path, line: &LogLine) -> io::Result<()> {
+    let mut files = OPEN_FILES
+        .lock()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+    if files.get(&line.server).is_some_and(needs_rotation) {
+        files.remove(&line.server);
+        rotate(dir, &line.server)?;
+        // 长时间运行时不会重启，轮转时顺便删除过期的文件
+        if let Err(e) = remove_expired_files(dir) {
+            eprintln!("Failed to remove expired log files: {}", e);
+        }
+    }
+    let active = match files.entry(line.server.clone()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(open_file(dir, &line.server)?),
+    };
+
+    let text = format!(
+        "{} [{}] {}\n",
+        line.timestamp,
+        line.stream.as_str(),
+        line.line
+    );
+    active.file.write_all(text.as_bytes())?;
+    active.size += text.len() as u64;
+    Ok(())
+}
+
+// 写入失败只打印错误，不影响内存中的日志
+pub fn append_line(line: &LogLine) {
+    let Ok(dir) = log_dir() else {
+        return;
+    };
+    if let Err(e) = write_line(&dir, line) {
+        eprintln!("Failed to write log file for {}: {}", line.server, e);
+    }
+}
+
+// 删除超过保留时间的已轮转文件
+fn remove_expired_files(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_rotated = path
+            .file_stem()
+            .and_then(|stem| Path::new(stem).extension())
+            .and_then(|index| index.to_str())
+            .is_some_and(|index| index.parse::<usize>().is_ok());
+        if !is_rotated {
+            continue;
+        }
+        let expired = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= RETENTION);
+        if expired {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn init_log_dir<R: Runtime>(app: &AppHandle<R>) {
+    let dir = match app.path().app_log_dir() {
+        Ok(dir) => dir.join("servers"),
+        Err(e) => {
+            eprintln!("Failed to get log directory: {}", e);
+            return;
+        }
+    };
+    if dir.exists() {
+        if let Err(e) = remove_expired_files(&dir) {
+            eprintln!("Failed to remove expired log files: {}", e);
+        }
+    }
+    if let Ok(mut current) = LOG_DIR.write() {
+        *current = Some(dir);
+    }
+}
+
+// 只接受 list_server_log_files 返回的文件名，避免读取日志目录以外的文件
+fn find_server_file(dir: &Path, server: &str, file_name: &str) -> Result<PathBuf, AppError> {
+    server_files(dir, server)
+        .into_iter()
+        .find(|path| path.file_name().is_some_and(|name| name == file_name))
+        .ok_or_else(|| {
+            AppError::io(
+                Message::LogReadFailed,
+                &dir.join(file_name),
+                io::Error::from(io::ErrorKind::NotFound),
+            )
+        })
+}
+
+#[tauri::command]
+pub fn list_server_log_files(name: String) -> Result<Vec<LogFileInfo>, AppError> {
+    let dir = log_dir()?;
+    let current = current_file_path(&dir, &name);
+    server_files(&dir, &name)
+        .into_iter()
+        .map(|path| {
+            let metadata =
+                fs::metadata(&path).map_err(|e| AppError::io(Message::LogReadFailed, &path, e))?;
+            let modified: DateTime<Local> = metadata
+                .modified()
+                .map_err(|e| AppError::io(Message::LogReadFailed, &path, e))?
+                .into();
+            Ok(LogFileInfo {
+                file_name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                size: metadata.len(),
+                modified: modified.to_rfc3339(),
+                current: path == current,
+            })
+        })
+        .collect()
+}
+
+// tail 为 Some 时只返回最后的 tail 行
+#[tauri::command]
+pub fn read_server_log_file(
+    name: String,
+    file_name: String,
+    tail: Option<usize>,
+) -> Result<String, AppError> {
+    let dir = log_dir()?;
+    let path = find_server_file(&dir, &name, &file_name)?;
+    let bytes = fs::read(&path).map_err(|e| AppError::io(Message::LogReadFailed, &path, e))?;
+    let content = String::from_utf8_lossy(&bytes);
+
+    match tail {
+        Some(tail) => {
+            let lines: Vec<&str> = content.lines().collect();
+            let start = lines.len().saturating_sub(tail);
+            Ok(lines[start..].join("\n"))
+        }
+        None => Ok(content.to_string()),
+    }
+}
+
+// 把该 server 的所有日志按从旧到新合并写入 destination
+#[tauri::command]
+pub fn export_server_logs(name: String, destination: String) -> Result<(), AppError> {
+    let dir = log_dir()?;
+    let destination = PathBuf::from(destination);
+    let mut output = File::create(&destination)
+        .map_err(|e| AppError::io(Message::LogWriteFailed, &destination, e))?;
+
+    for path in server_files(&dir, &name).into_iter().rev() {
+        let mut input =
+            File::open(&path).map_err(|e| AppError::io(Message::LogReadFailed, &path, e))?;
+        io::copy(&mut input, &mut output)
+            .map_err(|e| AppError::io(Message::LogWriteFailed, &destination, e))?;
+    }
+    Ok(())
+}
+
+// older_than_days 为 None 时删除全部日志，返回删除的文件数
+#[tauri::command]
+pub fn purge_server_logs(name: String, older_than_days: Option<u64>) -> Result<usize, AppError> {
+    let dir = log_dir()?;
+    let max_age =
+        older_than_days.map(|days| Duration::from_secs(days.saturating_mul(24 * 60 * 60)));
+
+    // 先关闭正在写入的文件，下次输出时重新创建
+    if max_age.is_none() {
+        if let Ok(mut files) = OPEN_FILES.lock() {
+            files.remove(&name);
+        }
+    }
+
+    let mut removed = 0;
+    for path in server_files(&dir, &name) {
+        let expired = match max_age {
+            Some(max_age) => fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age >= max_age),
+            None => true,
+        };
+        // 正在写入的文件仍有新内容，按时间清理时保留
+        if !expired || (max_age.is_some() && path == current_file_path(&dir, &name)) {
+            continue;
+        }
+        fs::remove_file(&path).map_err(|e| AppError::io(Message::LogWriteFailed, &path, e))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stems_do_not_collide() {
+        let stems: Vec<String> = ["foo.bar", "foo/bar", "foo_bar"]
+            .iter()
+            .map(|name| file_stem(name))
+            .collect();
+        assert!(stems.iter().all(|stem| stem.starts_with("foo_bar-")));
+        assert_ne!(stems[0], stems[1]);
+        assert_ne!(stems[1], stems[2]);
+        assert_ne!(stems[0], stems[2]);
+        assert!(!stems[0].contains('.'));
+        assert_eq!(file_stem("foo.bar"), stems[0]);
+    }
+
+    #[test]
+    fn rotation_keeps_the_newest_files() {
+        let dir = std::env::temp_dir().join(format!("mcp-log-files-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        for round in 0..=MAX_ROTATED_FILES + 1 {
+            fs::write(current_file_path(&dir, "server"), round.to_string()).unwrap();
+            rotate(&dir, "server").unwrap();
+        }
+
+        let files = server_files(&dir, "server");
+        assert_eq!(files.len(), MAX_ROTATED_FILES);
+        assert_eq!(
+            fs::read_to_string(&files[0]).unwrap(),
+            (MAX_ROTATED_FILES + 1).to_string()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
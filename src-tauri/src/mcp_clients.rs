@@ -0,0 +1,747 @@
+use crate::claude_config::{
+    config_conflict_error, content_revision, current_revision, merge_key_order, with_config_lock,
+    write_file_atomic,
+};
+use crate::config_diff::{diff_json, ConfigChange};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::{t_args, Message};
+use crate::server_entry::{McpServerEntry, RemoteTransport};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 支持管理 MCP server 配置的客户端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpClient {
+    ClaudeDesktop,
+    ClaudeCode,
+    Cursor,
+    Windsurf,
+    Vscode,
+    // 当前工作区的 .vscode/mcp.json
+    VscodeWorkspace,
+    Zed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct McpClientInfo {
+    pub id: McpClient,
+    pub name: &'static str,
+    pub config_path: String,
+    pub config_exists: bool,
+}
+
+pub fn home_dir() -> Result<PathBuf, AppError> {
+    let variable = if cfg!(target_os = "windows") {
+        "USERPROFILE"
+    } else {
+        "HOME"
+    };
+    env::var(variable)
+        .map(PathBuf::from)
+        .map_err(|_| AppError::home_not_found(variable))
+}
+
+// 各系统下应用的用户配置目录
+fn app_config_dir(app_name: &str) -> Result<PathBuf, AppError> {
+    let (dir, _) = crate::claude_config::user_config_dir()?;
+    Ok(dir.join(app_name))
+}
+
+impl McpClient {
+    pub const ALL: [McpClient; 7] = [
+        McpClient::ClaudeDesktop,
+        McpClient::ClaudeCode,
+        McpClient::Cursor,
+        McpClient::Windsurf,
+        McpClient::Vscode,
+        McpClient::VscodeWorkspace,
+        McpClient::Zed,
+    ];
+
+    // 与序列化后的名称一致
+    pub fn id(self) -> &'static str {
+        match self {
+            McpClient::ClaudeDesktop => "claude_desktop",
+            McpClient::ClaudeCode => "claude_code",
+            McpClient::Cursor => "cursor",
+            McpClient::Windsurf => "windsurf",
+            McpClient::Vscode => "vscode",
+            McpClient::VscodeWorkspace => "vscode_workspace",
+            McpClient::Zed => "zed",
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            McpClient::ClaudeDesktop => "Claude Desktop",
+            McpClient::ClaudeCode => "Claude Code",
+            McpClient::Cursor => "Cursor",
+            McpClient::Windsurf => "Windsurf",
+            McpClient::Vscode => "VS Code",
+            McpClient::VscodeWorkspace => "VS Code (Workspace)",
+            McpClient::Zed => "Zed",
+        }
+    }
+
+    pub fn config_path(self) -> Result<PathBuf, AppError> {
+        match self {
+            McpClient::ClaudeDesktop => crate::claude_config::config_file_path(),
+            McpClient::ClaudeCode => Ok(home_dir()?.join(".claude.json")),
+            McpClient::Cursor => Ok(home_dir()?.join(".cursor").join("mcp.json")),
+            McpClient::Windsurf => Ok(home_dir()?
+                .join(".codeium")
+                .join("windsurf")
+                .join("mcp_config.json")),
+            McpClient::Vscode => Ok(app_config_dir("Code")?.join("User").join("mcp.json")),
+            McpClient::VscodeWorkspace => {
+                let workspace = crate::variables::workspace_dir().ok_or_else(|| {
+                    AppError::localized(
+                        ErrorCode::ConfigPathInvalid,
+                        Message::WorkspaceNotSet,
+                        &[("client", "VS Code")],
+                    )
+                })?;
+                Ok(workspace.join(".vscode").join("mcp.json"))
+            }
+            McpClient::Zed => {
+                let dir = if cfg!(target_os = "windows") {
+                    app_config_dir("Zed")?
+                } else {
+                    home_dir()?.join(".config").join("zed")
+                };
+                Ok(dir.join("settings.json"))
+            }
+        }
+    }
+
+    // 快照保存在 app 的数据目录中，不在客户端自己的目录里留下文件；
+    // 工作区配置按路径区分，避免不同工作区的快照混在一起
+    pub fn backup_dir(self) -> Result<PathBuf, AppError> {
+        let dir = crate::config_backup::backup_root()?.join(self.id());
+        match self {
+            McpClient::VscodeWorkspace => {
+                let path = self.config_path()?;
+                let hash = content_revision(path.to_string_lossy().as_bytes());
+                Ok(dir.join(&hash[..12]))
+            }
+            _ => Ok(dir),
+        }
+    }
+
+    fn is_vscode(self) -> bool {
+        matches!(self, McpClient::Vscode | McpClient::VscodeWorkspace)
+    }
+
+    // 配置文件中保存 server 列表的字段
+    pub fn servers_key(self) -> &'static str {
+        match self {
+            McpClient::Vscode | McpClient::VscodeWorkspace => "servers",
+            McpClient::Zed => "context_servers",
+            _ => "mcpServers",
+        }
+    }
+
+    // Zed 的 settings.json 允许注释
+    fn allows_comments(self) -> bool {
+        self == McpClient::Zed
+    }
+
+    // 解析配置文件内容，空文件视为空配置
+    pub fn parse_document(
+        self,
+        path: &Path,
+        content: &str,
+    ) -> Result<Map<String, JsonValue>, AppError> {
+        let json = if self.allows_comments() {
+            strip_jsonc(content).0
+        } else {
+            content.to_string()
+        };
+
+        if json.trim().is_empty() {
+            return Ok(Map::new());
+        }
+        serde_json::from_str(&json).map_err(|e| AppError::config_parse(path, e))
+    }
+
+    // 把客户端格式的 server 配置转换为统一的 McpServerEntry
+    fn entry_from_client(self, value: &JsonValue) -> Result<McpServerEntry, AppError> {
+        let mut value = value.as_object().cloned().ok_or_else(|| {
+            AppError::localized(ErrorCode::ConfigInvalid, Message::ServerEntryNotObject, &[])
+        })?;
+
+        match self {
+            McpClient::Windsurf => {
+                if let Some(url) = value.shift_remove("serverUrl") {
+                    value.insert("url".to_string(), url);
+                }
+            }
+            McpClient::Vscode | McpClient::VscodeWorkspace | McpClient::ClaudeCode
+                if value.get("type").and_then(|v| v.as_str()) == Some("stdio") =>
+            {
+                value.shift_remove("type");
+            }
+            McpClient::Zed => {
+                // 旧版 Zed 格式: "command": { "path", "args", "env" }
+                if let Some(JsonValue::Object(command)) = value.shift_remove("command") {
+                    for (key, field) in command {
+                        let key = if key == "path" {
+                            "command".to_string()
+                        } else {
+                            key
+                        };
+                        value.insert(key, field);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        serde_json::from_value(JsonValue::Object(value)).map_err(|e| {
+            AppError::localized(
+                ErrorCode::ConfigInvalid,
+                Message::ServerEntryInvalid,
+                &[("error", &e.to_string())],
+            )
+        })
+    }
+
+    // 把 McpServerEntry 转换为客户端要求的格式
+    fn entry_to_client(self, entry: &McpServerEntry) -> Result<JsonValue, AppError> {
+        let mut value = match serde_json::to_value(entry) {
+            Ok(JsonValue::Object(value)) => value,
+            Ok(_) => {
+                return Err(AppError::localized(
+                    ErrorCode::ConfigInvalid,
+                    Message::ServerEntryNotObject,
+                    &[],
+                ))
+            }
+            Err(e) => {
+                return Err(AppError::internal(format!(
+                    "Failed to serialize server: {}",
+                    e
+                )))
+            }
+        };
+
+        match (self, entry) {
+            (McpClient::Cursor, McpServerEntry::Remote(_)) => {
+                value.shift_remove("type");
+            }
+            (McpClient::Windsurf, McpServerEntry::Remote(_)) => {
+                value.shift_remove("type");
+                if let Some(url) = value.shift_remove("url") {
+                    value.insert("serverUrl".to_string(), url);
+                }
+            }
+            // VS Code 和 Claude Code 要求显式写出传输类型
+            (
+                McpClient::Vscode | McpClient::VscodeWorkspace | McpClient::ClaudeCode,
+                McpServerEntry::Stdio(_),
+            ) => {
+                value.shift_insert(0, "type".to_string(), JsonValue::from("stdio"));
+            }
+            (
+                McpClient::Vscode | McpClient::VscodeWorkspace | McpClient::ClaudeCode,
+                McpServerEntry::Remote(remote),
+            ) => {
+                let transport = match remote.effective_transport() {
+                    RemoteTransport::Sse => "sse",
+                    RemoteTransport::StreamableHttp => "http",
+                };
+                value.insert("type".to_string(), JsonValue::from(transport));
+            }
+            (McpClient::Zed, McpServerEntry::Stdio(_)) if !value.contains_key("source") => {
+                value.shift_insert(0, "source".to_string(), JsonValue::from("custom"));
+            }
+            _ => {}
+        }
+
+        Ok(JsonValue::Object(value))
+    }
+}
+
+// 跳过空白和注释后的下一个字符
+fn next_significant(mut chars: std::iter::Peekable<std::str::Chars>) -> Option<char> {
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            (c, _) if c.is_whitespace() => {}
+            ('/', Some('/')) => {
+                chars.find(|&c| c == '\n')?;
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => return Some(c),
+        }
+    }
+    None
+}
+
+// 去掉 JSONC 中的注释和尾随逗号，返回去除后的内容以及是否包含注释
+fn strip_jsonc(content: &str) -> (String, bool) {
+    let mut output = String::with_capacity(content.len());
+    let mut had_comments = false;
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    output.push(escaped);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                had_comments = true;
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                had_comments = true;
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            (',', _) => {
+                // 逗号后面（忽略注释）紧跟右括号时丢弃这个逗号，
+                // 例如 Zed 配置中被注释掉的最后一项
+                if !matches!(next_significant(chars.clone()), Some('}') | Some(']')) {
+                    output.push(c);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    (output, had_comments)
+}
+
+// 文本中 ${input:id} 引用的 id
+fn input_references(text: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${input:") {
+        let after = &rest[start + "${input:".len()..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        if end > 0 {
+            ids.push(&after[..end]);
+        }
+        rest = &after[end + 1..];
+    }
+    ids
+}
+
+// 某个客户端的完整配置文件
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub client: McpClient,
+    pub document: Map<String, JsonValue>,
+    // 读取时的文件哈希，文件不存在时为 None
+    pub revision: Option<String>,
+    had_comments: bool,
+}
+
+impl ClientConfig {
+    fn servers_object(&self) -> Option<&Map<String, JsonValue>> {
+        self.document
+            .get(self.client.servers_key())
+            .and_then(|v| v.as_object())
+    }
+
+    pub fn server_names(&self) -> Vec<String> {
+        self.servers_object()
+            .map(|servers| servers.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn contains_server(&self, name: &str) -> bool {
+        self.servers_object()
+            .is_some_and(|servers| servers.contains_key(name))
+    }
+
+    pub fn servers(&self) -> Result<IndexMap<String, McpServerEntry>, AppError> {
+        let mut servers = IndexMap::new();
+        if let Some(values) = self.servers_object() {
+            for (name, value) in values {
+                let entry = self
+                    .client
+                    .entry_from_client(value)
+                    .map_err(|e| server_config_error(name, e))?;
+                servers.insert(name.clone(), entry);
+            }
+        }
+        Ok(servers)
+    }
+
+    // servers 对象不存在时创建
+    fn servers_object_mut(&mut self) -> Result<&mut Map<String, JsonValue>, AppError> {
+        let key = self.client.servers_key();
+
+        match self
+            .document
+            .entry(key)
+            .or_insert_with(|| JsonValue::Object(Map::new()))
+        {
+            JsonValue::Object(servers) => Ok(servers),
+            _ => Err(AppError::localized(
+                ErrorCode::ConfigInvalid,
+                Message::ServersNotObject,
+                &[("key", key)],
+            )
+            .with("key", key)),
+        }
+    }
+
+    // server 在配置中的位置
+    pub fn server_position(&self, name: &str) -> Option<usize> {
+        self.servers_object()
+            .and_then(|servers| servers.keys().position(|key| key == name))
+    }
+
+    // 添加或替换 server，已存在时保留其位置和字段顺序
+    pub fn set_server(&mut self, name: &str, entry: &McpServerEntry) -> Result<(), AppError> {
+        let value = self.client.entry_to_client(entry)?;
+        let servers = self.servers_object_mut()?;
+
+        let value = match servers.get(name) {
+            Some(existing) => merge_key_order(existing, value),
+            None => value,
+        };
+        servers.insert(name.to_string(), value);
+
+        if self.client.is_vscode() {
+            self.add_missing_inputs(entry)?;
+        }
+        Ok(())
+    }
+
+    // VS Code 通过 ${input:id} 引用 inputs 中定义的值，启动 server 时提示用户输入；
+    // 为还没有定义的 id 补上密码类型的输入项，已有的定义保持不变
+    fn add_missing_inputs(&mut self, entry: &McpServerEntry) -> Result<(), AppError> {
+        let value = serde_json::to_value(entry)
+            .map_err(|e| AppError::internal(format!("Failed to serialize server: {}", e)))?;
+        let mut ids: Vec<String> = Vec::new();
+        crate::secrets::walk_strings(&value, String::new(), &mut |_, text| {
+            for id in input_references(text) {
+                if !ids.iter().any(|existing| existing == id) {
+                    ids.push(id.to_string());
+                }
+            }
+        });
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let inputs = match self
+            .document
+            .entry("inputs")
+            .or_insert_with(|| JsonValue::Array(Vec::new()))
+        {
+            JsonValue::Array(inputs) => inputs,
+            _ => {
+                return Err(AppError::localized(
+                    ErrorCode::ConfigInvalid,
+                    Message::FieldNotArray,
+                    &[("key", "inputs")],
+                )
+                .with("key", "inputs"))
+            }
+        };
+        for id in ids {
+            let defined = inputs
+                .iter()
+                .any(|input| input.get("id").and_then(|v| v.as_str()) == Some(id.as_str()));
+            if !defined {
+                inputs.push(serde_json::json!({
+                    "type": "promptString",
+                    "id": id,
+                    "description": id,
+                    "password": true,
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    // 将客户端格式的原始配置放回指定位置，不做任何转换
+    pub fn insert_server_value(
+        &mut self,
+        name: &str,
+        position: usize,
+        value: JsonValue,
+    ) -> Result<(), AppError> {
+        let servers = self.servers_object_mut()?;
+        let position = position.min(servers.len());
+        servers.shift_insert(position, name.to_string(), value);
+        Ok(())
+    }
+
+    pub fn remove_server(&mut self, name: &str) -> Option<JsonValue> {
+        self.document
+            .get_mut(self.client.servers_key())
+            .and_then(|v| v.as_object_mut())
+            .and_then(|servers| servers.shift_remove(name))
+    }
+}
+
+// 某个 server 的配置有误，错误信息中带上 server 名称
+pub fn server_config_error(name: &str, e: AppError) -> AppError {
+    AppError {
+        message: t_args(
+            Message::ServerConfigError,
+            &[("server", name), ("error", &e.message)],
+        ),
+        ..e
+    }
+    .with("server", name)
+}
+
+// 读取客户端配置，文件不存在时返回只有空 server 列表的最小配置，
+// 第一次修改时会连同目录一起创建
+pub fn read_client_config(client: McpClient) -> Result<ClientConfig, AppError> {
+    let path = client.config_path()?;
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mut document = Map::new();
+            document.insert(
+                client.servers_key().to_string(),
+                JsonValue::Object(Map::new()),
+            );
+            return Ok(ClientConfig {
+                client,
+                document,
+                revision: None,
+                had_comments: false,
+            });
+        }
+        Err(e) => return Err(AppError::io(Message::ConfigReadFailed, &path, e)),
+    };
+
+    let had_comments = client.allows_comments() && strip_jsonc(&content).1;
+
+    Ok(ClientConfig {
+        client,
+        document: client.parse_document(&path, &content)?,
+        revision: Some(content_revision(content.as_bytes())),
+        had_comments,
+    })
+}
+
+// 写入客户端配置，调用方需要持有配置锁
+pub fn write_client_config(config: &ClientConfig) -> Result<(), AppError> {
+    let path = config.client.config_path()?;
+
+    // 重新写入会丢失注释，这种情况下不自动修改
+    if config.had_comments {
+        return Err(AppError::localized(
+            ErrorCode::ConfigUnsupported,
+            Message::ConfigHasComments,
+            &[
+                ("client", config.client.display_name()),
+                ("path", &path.display().to_string()),
+            ],
+        )
+        .with("path", path.to_string_lossy().to_string()));
+    }
+
+    if current_revision(&path)? != config.revision {
+        return Err(config_conflict_error().with("path", path.to_string_lossy().to_string()));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io(Message::ConfigDirCreateFailed, parent, e))?;
+    }
+
+    let content = serde_json::to_string_pretty(&config.document)
+        .map_err(|e| AppError::internal(format!("Failed to serialize config: {}", e)))?;
+
+    write_file_atomic(&path, content.as_bytes())
+}
+
+// 在配置锁内完成 读取 -> 修改 -> 备份 -> 写入
+pub fn modify_client_config<T, E: From<AppError>>(
+    client: McpClient,
+    f: impl FnOnce(&mut ClientConfig) -> Result<T, E>,
+) -> Result<T, E> {
+    with_config_lock(|| {
+        let mut config = read_client_config(client)?;
+        let before = serde_json::to_string(&config.document).unwrap_or_default();
+        let result = f(&mut config)?;
+
+        // 修改失败或内容没有变化时不创建快照，以免挤掉真正的历史
+        if serde_json::to_string(&config.document).unwrap_or_default() == before {
+            return Ok(result);
+        }
+
+        crate::config_backup::backup_config(client)?;
+        write_client_config(&config)?;
+
+        Ok(result)
+    })
+}
+
+// 修改前后的完整配置及差异
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigPreview {
+    pub client: McpClient,
+    pub path: String,
+    pub before: JsonValue,
+    pub after: JsonValue,
+    pub changes: Vec<ConfigChange>,
+}
+
+// 与 modify_client_config 相同，但返回修改前后的差异；
+// dry_run 时只计算结果，不备份也不写入文件
+pub fn change_client_config<E: From<AppError>>(
+    client: McpClient,
+    dry_run: bool,
+    f: impl FnOnce(&mut ClientConfig) -> Result<(), E>,
+) -> Result<ConfigPreview, E> {
+    let run = |config: &mut ClientConfig| -> Result<ConfigPreview, E> {
+        let before = JsonValue::Object(config.document.clone());
+        f(config)?;
+        let after = JsonValue::Object(config.document.clone());
+
+        Ok(ConfigPreview {
+            client,
+            path: client.config_path()?.to_string_lossy().to_string(),
+            changes: diff_json(&before, &after),
+            before,
+            after,
+        })
+    };
+
+    if dry_run {
+        run(&mut read_client_config(client)?)
+    } else {
+        modify_client_config(client, run)
+    }
+}
+
+// 未指定客户端时默认操作 Claude Desktop
+pub fn target_clients(clients: Option<Vec<McpClient>>) -> Vec<McpClient> {
+    match clients {
+        Some(clients) if !clients.is_empty() => clients,
+        _ => vec![McpClient::ClaudeDesktop],
+    }
+}
+
+#[tauri::command]
+pub fn list_mcp_clients() -> Vec<McpClientInfo> {
+    McpClient::ALL
+        .iter()
+        .map(|&client| {
+            let path = client.config_path().ok();
+            McpClientInfo {
+                id: client,
+                name: client.display_name(),
+                config_path: path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                config_exists: path.is_some_and(|path| path.exists()),
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_mcp_servers(
+    client: Option<McpClient>,
+) -> Result<IndexMap<String, McpServerEntry>, AppError> {
+    let config = read_client_config(client.unwrap_or(McpClient::ClaudeDesktop))?;
+    config.servers()
+}
+
+// 查询 server 已安装到哪些客户端
+#[tauri::command]
+pub fn get_server_clients(name: String) -> Vec<McpClient> {
+    McpClient::ALL
+        .iter()
+        .copied()
+        .filter(|&client| {
+            read_client_config(client)
+                .map(|config| config.contains_server(&name))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip_jsonc;
+
+    fn parse(content: &str) -> serde_json::Value {
+        serde_json::from_str(&strip_jsonc(content).0).unwrap()
+    }
+
+    #[test]
+    fn drops_trailing_comma_before_comment() {
+        let content = "{\n  \"theme\": \"x\",\n  // \"vim_mode\": true\n}";
+        assert_eq!(parse(content), serde_json::json!({ "theme": "x" }));
+
+        let content = "[1, /* 2 */\n]";
+        assert_eq!(parse(content), serde_json::json!([1]));
+    }
+
+    #[test]
+    fn keeps_comment_markers_inside_strings() {
+        let (output, had_comments) = strip_jsonc(r#"{"url": "https://example.com/*x*/"}"#);
+        assert!(!had_comments);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&output).unwrap(),
+            serde_json::json!({ "url": "https://example.com/*x*/" })
+        );
+    }
+
+    #[test]
+    fn handles_escaped_quotes() {
+        let content = r#"{"a": "say \"hi\" // not a comment", "b": 1, // comment
+        }"#;
+        let (output, had_comments) = strip_jsonc(content);
+        assert!(had_comments);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&output).unwrap(),
+            serde_json::json!({ "a": "say \"hi\" // not a comment", "b": 1 })
+        );
+    }
+}
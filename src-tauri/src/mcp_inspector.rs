@@ -0,0 +1,323 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::mcp_runner::server_session;
+use crate::mcp_session::McpSession;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value as JsonValue};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+// 工具调用可能较慢，等待时间比握手更长
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+// 分页请求的最大页数，防止 server 一直返回 nextCursor
+const MAX_PAGES: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTool {
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub input_schema: JsonValue,
+    #[serde(default)]
+    pub output_schema: Option<JsonValue>,
+    #[serde(default)]
+    pub annotations: Option<JsonValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub annotations: Option<JsonValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub annotations: Option<JsonValue>,
+}
+
+// resources/read 返回的一段内容，二进制内容保持 base64 编码
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ResourceContent {
+    Text {
+        uri: String,
+        mime_type: Option<String>,
+        text: String,
+    },
+    Binary {
+        uri: String,
+        mime_type: Option<String>,
+        blob: String,
+        // 解码后的字节数，无法解码时为 None
+        size: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPrompt {
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptResult {
+    pub description: Option<String>,
+    // 原样返回的 PromptMessage，包含 role 和 content
+    pub messages: Vec<JsonValue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCallResult {
+    pub content: Vec<JsonValue>,
+    pub structured_content: Option<JsonValue>,
+    // 工具本身报告的错误，与协议错误不同，结果中仍有 content
+    pub is_error: bool,
+    pub duration_ms: u64,
+}
+
+// 在阻塞线程中使用 server 的会话，避免占用异步运行时
+async fn with_session<T, F>(name: String, f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&McpSession) -> Result<T, AppError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || {
+        let session = server_session(&name)?;
+        f(&session)
+    })
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to run request: {}", e)))?
+}
+
+// 依次请求各页，合并 result 中 key 对应的数组
+fn list_all(session: &McpSession, method: &str, key: &str) -> Result<Vec<JsonValue>, AppError> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut seen_cursors = HashSet::new();
+    for _ in 0..MAX_PAGES {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let result = session.request(method, params, REQUEST_TIMEOUT)?;
+        if let Some(page) = result.get(key).and_then(|page| page.as_array()) {
+            items.extend(page.iter().cloned());
+        }
+        cursor = result
+            .get("nextCursor")
+            .and_then(|cursor| cursor.as_str())
+            .map(|cursor| cursor.to_string());
+        match &cursor {
+            None => return Ok(items),
+            // 重复的 cursor 会导致无限循环
+            Some(cursor) if !seen_cursors.insert(cursor.clone()) => break,
+            Some(_) => {}
+        }
+    }
+
+    Err(AppError::localized(
+        ErrorCode::RpcError,
+        Message::PaginationNotFinished,
+        &[("method", method)],
+    )
+    .with("method", method))
+}
+
+// 跳过格式不对的条目，不影响其它条目的显示
+fn list_typed<T: DeserializeOwned>(
+    session: &McpSession,
+    method: &str,
+    key: &str,
+) -> Result<Vec<T>, AppError> {
+    Ok(list_all(session, method, key)?
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item).ok())
+        .collect())
+}
+
+fn string_field(value: &JsonValue, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|field| field.as_str())
+        .map(|field| field.to_string())
+}
+
+fn resource_content(content: &JsonValue) -> Option<ResourceContent> {
+    let uri = string_field(content, "uri")?;
+    let mime_type = string_field(content, "mimeType");
+    if let Some(text) = string_field(content, "text") {
+        return Some(ResourceContent::Text {
+            uri,
+            mime_type,
+            text,
+        });
+    }
+    let blob = string_field(content, "blob")?;
+    Some(ResourceContent::Binary {
+        uri,
+        mime_type,
+        size: BASE64.decode(&blob).ok().map(|bytes| bytes.len()),
+        blob,
+    })
+}
+
+#[tauri::command]
+pub async fn list_server_tools(name: String) -> Result<Vec<McpTool>, AppError> {
+    with_session(name, |session| list_typed(session, "tools/list", "tools")).await
+}
+
+#[tauri::command]
+pub async fn call_server_tool(
+    name: String,
+    tool: String,
+    arguments: Option<JsonValue>,
+) -> Result<ToolCallResult, AppError> {
+    let arguments = match arguments {
+        None | Some(JsonValue::Null) => JsonValue::Object(Map::new()),
+        Some(arguments @ JsonValue::Object(_)) => arguments,
+        Some(_) => {
+            return Err(AppError::localized(
+                ErrorCode::InvalidArguments,
+                Message::ToolArgumentsNotObject,
+                &[("tool", &tool)],
+            )
+            .with("tool", tool.as_str()))
+        }
+    };
+
+    with_session(name, move |session| {
+        let started = Instant::now();
+        let result = session.request(
+            "tools/call",
+            json!({ "name": tool, "arguments": arguments }),
+            REQUEST_TIMEOUT,
+        )?;
+
+        Ok(ToolCallResult {
+            content: result
+                .get("content")
+                .and_then(|content| content.as_array())
+                .cloned()
+                .unwrap_or_default(),
+            structured_content: result.get("structuredContent").cloned(),
+            is_error: result
+                .get("isError")
+                .and_then(|is_error| is_error.as_bool())
+                .unwrap_or(false),
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_server_resources(name: String) -> Result<Vec<McpResource>, AppError> {
+    with_session(name, |session| {
+        list_typed(session, "resources/list", "resources")
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_server_resource_templates(
+    name: String,
+) -> Result<Vec<McpResourceTemplate>, AppError> {
+    with_session(name, |session| {
+        list_typed(session, "resources/templates/list", "resourceTemplates")
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn read_server_resource(
+    name: String,
+    uri: String,
+) -> Result<Vec<ResourceContent>, AppError> {
+    with_session(name, move |session| {
+        let result = session.request("resources/read", json!({ "uri": uri }), REQUEST_TIMEOUT)?;
+        Ok(result
+            .get("contents")
+            .and_then(|contents| contents.as_array())
+            .map(|contents| contents.iter().filter_map(resource_content).collect())
+            .unwrap_or_default())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_server_prompts(name: String) -> Result<Vec<McpPrompt>, AppError> {
+    with_session(name, |session| {
+        list_typed(session, "prompts/list", "prompts")
+    })
+    .await
+}
+
+// 协议规定 prompt 的参数值都是字符串
+#[tauri::command]
+pub async fn get_server_prompt(
+    name: String,
+    prompt: String,
+    arguments: Option<IndexMap<String, String>>,
+) -> Result<PromptResult, AppError> {
+    with_session(name, move |session| {
+        let result = session.request(
+            "prompts/get",
+            json!({ "name": prompt, "arguments": arguments.unwrap_or_default() }),
+            REQUEST_TIMEOUT,
+        )?;
+        Ok(PromptResult {
+            description: string_field(&result, "description"),
+            messages: result
+                .get("messages")
+                .and_then(|messages| messages.as_array())
+                .cloned()
+                .unwrap_or_default(),
+        })
+    })
+    .await
+}
@@ -1,110 +1,333 @@
+use crate::config_validation::{expand_home, resolve_command};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::mcp_session::{McpSession, ServerHandshake};
+use crate::server_entry::{McpServerEntry, RemoteServerConfig, RemoteTransport, StdioServerConfig};
+use crate::server_logs::{capture_output, LogStream};
 use crate::tray::update_tray_status;
+use indexmap::IndexMap;
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
-use std::process::{Child, Command, Stdio};
-use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use tauri::AppHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+
+// 完成握手的本地 server
+struct RunningServer {
+    child: Child,
+    session: Arc<McpSession>,
+    handshake: ServerHandshake,
+    started_at: Instant,
+}
+
+// 自行退出的本地 server，由 supervisor 处理
+pub struct ExitedServer {
+    pub name: String,
+    pub status: ExitStatus,
+    pub uptime: Duration,
+}
 
 // 存储运行中的 servers 和它们的状态
-static RUNNING_SERVERS: Lazy<Mutex<HashMap<String, Child>>> =
+static RUNNING_SERVERS: Lazy<Mutex<HashMap<String, RunningServer>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-pub fn start_mcp_server(
-    name: &str,
-    command: &str,
-    args: &[String],
-    env: Option<&HashMap<String, String>>,
-) -> Result<(), String> {
+// 未设置时等待 initialize 响应的秒数
+pub const DEFAULT_HANDSHAKE_TIMEOUT_SECS: u64 = 30;
+
+// 远程 server 没有本地进程，只记录已确认可连接的名称
+static CONNECTED_REMOTE_SERVERS: Lazy<Mutex<HashSet<String>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
+// 子进程从 MCP Manager 继承哪些环境变量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvInheritPolicy {
+    // 与 Claude Desktop 等客户端使用的 MCP SDK 相同，只继承少量基础变量
+    #[default]
+    Minimal,
+    // 继承全部环境变量
+    All,
+    // 只使用配置中的 env
+    None,
+}
+
+// MCP SDK 默认继承的环境变量
+#[cfg(windows)]
+const MINIMAL_INHERITED_ENV: &[&str] = &[
+    "APPDATA",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "LOCALAPPDATA",
+    "PATH",
+    "PROCESSOR_ARCHITECTURE",
+    "SYSTEMDRIVE",
+    "SYSTEMROOT",
+    "TEMP",
+    "USERNAME",
+    "USERPROFILE",
+    "PROGRAMFILES",
+];
+#[cfg(not(windows))]
+const MINIMAL_INHERITED_ENV: &[&str] = &["HOME", "LOGNAME", "PATH", "SHELL", "TERM", "USER"];
+
+// 实际启动进程时使用的命令、参数、工作目录和完整的环境变量
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchSpec {
+    pub name: String,
+    pub command: String,
+    // 在 PATH 中找到的可执行文件，找不到时为 None
+    pub resolved_command: Option<String>,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: IndexMap<String, String>,
+    pub inherit_env: EnvInheritPolicy,
+}
+
+impl LaunchSpec {
+    // stdio 为已经展开变量和密钥引用的配置
+    pub fn new(name: &str, stdio: &StdioServerConfig, inherit_env: EnvInheritPolicy) -> Self {
+        let mut env_vars: IndexMap<String, String> = match inherit_env {
+            EnvInheritPolicy::All => env::vars().collect(),
+            EnvInheritPolicy::Minimal => MINIMAL_INHERITED_ENV
+                .iter()
+                .filter_map(|key| {
+                    env::var(key)
+                        .ok()
+                        // SDK 不继承以 () 开头的 shell 函数
+                        .filter(|value| !value.starts_with("()"))
+                        .map(|value| (key.to_string(), value))
+                })
+                .collect(),
+            EnvInheritPolicy::None => IndexMap::new(),
+        };
+        // 配置中的 env 覆盖继承的变量
+        if let Some(server_env) = &stdio.env {
+            env_vars.extend(server_env.clone());
+        }
+
+        LaunchSpec {
+            name: name.to_string(),
+            command: stdio.command.clone(),
+            resolved_command: resolve_command(&stdio.command)
+                .map(|path| path.to_string_lossy().to_string()),
+            args: stdio.args.clone(),
+            cwd: stdio
+                .cwd
+                .as_deref()
+                .map(|cwd| expand_home(cwd).to_string_lossy().to_string()),
+            env: env_vars,
+            inherit_env,
+        }
+    }
+}
+
+// 进程关闭 stdout 后通常很快退出，稍等一下以便拿到退出状态
+fn exit_status_soon(child: &mut Child) -> Option<ExitStatus> {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            _ => return None,
+        }
+    }
+}
+
+// 进程仍在运行；已退出但还没被 supervisor 移除的不算
+fn is_running(servers: &mut HashMap<String, RunningServer>, name: &str) -> bool {
+    servers
+        .get_mut(name)
+        .is_some_and(|server| matches!(server.child.try_wait(), Ok(None)))
+}
+
+// 启动进程并完成 initialize 握手，握手失败时结束进程
+pub fn start_mcp_server(spec: &LaunchSpec, handshake_timeout: Duration) -> Result<(), AppError> {
+    let name = spec.name.as_str();
+    let command = spec.command.as_str();
+
+    // 已经在运行时不再启动第二个进程
+    let already_running = RUNNING_SERVERS
+        .lock()
+        .map(|mut servers| is_running(&mut servers, name))
+        .unwrap_or(false);
+    if already_running {
+        println!("MCP server {} is already running", name);
+        return Ok(());
+    }
+
     println!(
         "Starting MCP server: {} with command: {} {}",
         name,
         command,
-        args.join(" ")
+        spec.args.join(" ")
     );
 
-    let mut command = Command::new(command);
-    command
-        .args(args)
+    let start_failed = |message: Message, args: &[(&str, &str)]| {
+        AppError::localized(ErrorCode::ServerStartFailed, message, args)
+            .with("server", name)
+            .with("command", command)
+    };
+
+    // 与预览中显示的路径一致；不继承 PATH 时裸命令名可能找不到
+    let mut process = Command::new(spec.resolved_command.as_deref().unwrap_or(command));
+    process
+        .args(&spec.args)
+        .env_clear()
+        .envs(&spec.env)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    if let Some(env_vars) = env {
-        for (key, value) in env_vars {
-            command.env(key, value);
-        }
+    if let Some(cwd) = &spec.cwd {
+        process.current_dir(cwd);
     }
 
-    match command.spawn() {
+    match process.spawn() {
         Ok(mut child) => {
-            // 获取进程的输出流
-            let stdout = child
-                .stdout
-                .take()
-                .ok_or_else(|| "Failed to capture stdout".to_string())?;
-            let stderr = child
-                .stderr
-                .take()
-                .ok_or_else(|| "Failed to capture stderr".to_string())?;
-
-            let name_clone = name.to_string();
-            // 在新线程中监控输出
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        println!("[{}] stdout: {}", name_clone, line);
-                    }
-                }
-            });
+            // 获取进程的输入输出流
+            let (Some(stdin), Some(stdout), Some(stderr)) =
+                (child.stdin.take(), child.stdout.take(), child.stderr.take())
+            else {
+                let _ = child.kill();
+                return Err(start_failed(
+                    Message::ServerStartFailed,
+                    &[("error", "Failed to capture stdio")],
+                ));
+            };
 
-            let name_clone = name.to_string();
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        eprintln!("[{}] stderr: {}", name_clone, line);
-                    }
-                }
+            // stdout 是 JSON-RPC 通道，两个输出流都记录到该 server 的日志中
+            let session = Arc::new(McpSession::new(name, stdin));
+            let reader_session = session.clone();
+            capture_output(name, LogStream::Stdout, stdout, move |line| match line {
+                Some(line) => reader_session.handle_line(line),
+                None => reader_session.close(),
             });
+            capture_output(name, LogStream::Stderr, stderr, |_| {});
 
-            // 等待一小段时间检查进程是否存活
-            thread::sleep(std::time::Duration::from_millis(500));
-
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    return Err(format!(
-                        "MCP server failed to start: process exited with status {}",
-                        status
-                    ));
-                }
-                Ok(None) => {
-                    println!("Successfully started MCP server: {}", name);
-                    let mut servers = RUNNING_SERVERS
-                        .lock()
-                        .map_err(|e| format!("Failed to lock servers: {}", e))?;
-                    servers.insert(name.to_string(), child);
+            match session.initialize(handshake_timeout) {
+                Ok(handshake) => {
+                    println!(
+                        "Successfully started MCP server: {} (protocol {})",
+                        name, handshake.protocol_version
+                    );
+                    let mut servers = RUNNING_SERVERS.lock().map_err(|e| {
+                        AppError::internal(format!("Failed to lock servers: {}", e))
+                    })?;
+                    // 握手期间另一次启动已经完成时，保留先启动的进程
+                    if is_running(&mut servers, name) {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Ok(());
+                    }
+                    servers.insert(
+                        name.to_string(),
+                        RunningServer {
+                            child,
+                            session,
+                            handshake,
+                            started_at: Instant::now(),
+                        },
+                    );
                     Ok(())
                 }
-                Err(e) => Err(format!("Failed to check process status: {}", e)),
+                Err(error) => {
+                    // 进程已经退出时，退出状态比握手错误更有用
+                    if let Some(status) = exit_status_soon(&mut child) {
+                        let error =
+                            start_failed(Message::ServerExited, &[("status", &status.to_string())]);
+                        return Err(match status.code() {
+                            Some(code) => error.with("exit_code", code),
+                            None => error,
+                        });
+                    }
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    Err(error.with("command", command))
+                }
             }
         }
         Err(e) => {
-            let error = format!("Failed to start MCP server: {}", e);
+            let error = start_failed(Message::ServerStartFailed, &[("error", &e.to_string())]);
             eprintln!("{}", error);
             Err(error)
         }
     }
 }
 
-pub fn stop_mcp_server(name: &str) -> Result<(), String> {
+// 检查远程 server 的地址是否可以访问，成功后标记为运行中
+pub async fn connect_remote_server(
+    name: &str,
+    remote: &RemoteServerConfig,
+) -> Result<(), AppError> {
+    println!("Connecting to remote MCP server: {} at {}", name, remote.url);
+
+    let connect_failed = |message: Message, args: &[(&str, &str)]| {
+        AppError::localized(ErrorCode::RemoteConnectFailed, message, args)
+            .with("server", name)
+            .with("url", remote.url.as_str())
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| AppError::internal(format!("Failed to create HTTP client: {}", e)))?;
+
+    let accept = match remote.effective_transport() {
+        RemoteTransport::Sse => "text/event-stream",
+        RemoteTransport::StreamableHttp => "application/json, text/event-stream",
+    };
+    let mut request = client.get(&remote.url).header("Accept", accept);
+    if let Some(headers) = &remote.headers {
+        for (key, value) in headers {
+            request = request.header(key.as_str(), value.as_str());
+        }
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| connect_failed(Message::RemoteConnectFailed, &[("error", &e.to_string())]))?;
+
+    // Streamable HTTP 的 server 可能不支持 GET (405)，只要能响应就认为可用
+    let status = response.status();
+    if status.is_server_error()
+        || status == reqwest::StatusCode::NOT_FOUND
+        || status == reqwest::StatusCode::UNAUTHORIZED
+        || status == reqwest::StatusCode::FORBIDDEN
+    {
+        return Err(
+            connect_failed(Message::RemoteBadStatus, &[("status", &status.to_string())])
+                .with("status", status.as_u16()),
+        );
+    }
+
+    CONNECTED_REMOTE_SERVERS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock servers: {}", e)))?
+        .insert(name.to_string());
+
+    println!("Successfully connected to remote MCP server: {}", name);
+    Ok(())
+}
+
+pub fn stop_mcp_server(name: &str) -> Result<(), AppError> {
+    // 手动停止后不再自动重启
+    crate::supervisor::cancel_restart(name);
+
+    CONNECTED_REMOTE_SERVERS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock servers: {}", e)))?
+        .remove(name);
+
     let mut servers = RUNNING_SERVERS
         .lock()
-        .map_err(|e| format!("Failed to lock servers: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to lock servers: {}", e)))?;
 
-    if let Some(mut child) = servers.remove(name) {
+    if let Some(RunningServer { mut child, .. }) = servers.remove(name) {
         // 先尝试优雅地终止进程
         if let Err(e) = child.kill() {
             eprintln!("Failed to kill process: {}", e);
@@ -119,7 +342,7 @@ pub fn stop_mcp_server(name: &str) -> Result<(), String> {
             #[cfg(windows)]
             {
                 let _ = std::process::Command::new("taskkill")
-                    .args(&["/F", "/PID", &child.id().to_string()])
+                    .args(["/F", "/PID", &child.id().to_string()])
                     .spawn();
             }
         }
@@ -138,13 +361,20 @@ pub fn stop_mcp_server(name: &str) -> Result<(), String> {
     Ok(())
 }
 
-pub fn stop_all_servers() -> Result<(), String> {
+pub fn stop_all_servers() -> Result<(), AppError> {
+    crate::supervisor::cancel_all_restarts();
+
+    CONNECTED_REMOTE_SERVERS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock servers: {}", e)))?
+        .clear();
+
     let mut servers = RUNNING_SERVERS
         .lock()
-        .map_err(|e| format!("Failed to lock servers: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to lock servers: {}", e)))?;
 
-    for (name, mut child) in servers.drain() {
-        if let Err(e) = child.kill() {
+    for (name, mut server) in servers.drain() {
+        if let Err(e) = server.child.kill() {
             eprintln!("Failed to stop MCP server {}: {}", name, e);
         }
     }
@@ -152,82 +382,203 @@ pub fn stop_all_servers() -> Result<(), String> {
     Ok(())
 }
 
-#[tauri::command]
-pub fn get_server_status(name: &str) -> bool {
-    if let Ok(mut servers) = RUNNING_SERVERS.lock() {
-        // 先检查服务器是否存在
-        if !servers.contains_key(name) {
-            return false;
-        }
+// 移除已经退出的进程并返回它们的退出状态
+pub fn take_exited_servers() -> Vec<ExitedServer> {
+    let Ok(mut servers) = RUNNING_SERVERS.lock() else {
+        return Vec::new();
+    };
 
-        // 尝试检查进程状态并根据需要移除
-        let should_remove = if let Some(child) = servers.get_mut(name) {
-            match child.try_wait() {
-                Ok(None) => false,   // 进程运行中，不需要移除
-                Ok(Some(_)) => true, // 进程已退出，需要移除
-                Err(_) => true,      // 检查失败，需要移除
+    let exited: Vec<(String, ExitStatus)> = servers
+        .iter_mut()
+        .filter_map(|(name, server)| match server.child.try_wait() {
+            Ok(Some(status)) => Some((name.clone(), status)),
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("Failed to check status of {}: {}", name, e);
+                None
             }
-        } else {
-            false
-        };
+        })
+        .collect();
 
-        // 如果需要移除，就移除进程
-        if should_remove {
-            servers.remove(name);
-            false
-        } else {
-            true
+    exited
+        .into_iter()
+        .filter_map(|(name, status)| {
+            let server = servers.remove(&name)?;
+            Some(ExitedServer {
+                name,
+                status,
+                uptime: server.started_at.elapsed(),
+            })
+        })
+        .collect()
+}
+
+// 已退出的进程留给 supervisor 移除，以便记录崩溃并按策略重启
+#[tauri::command]
+pub fn get_server_status(name: &str) -> bool {
+    if let Ok(remote_servers) = CONNECTED_REMOTE_SERVERS.lock() {
+        if remote_servers.contains(name) {
+            return true;
         }
-    } else {
-        false
     }
+
+    RUNNING_SERVERS
+        .lock()
+        .ok()
+        .and_then(|mut servers| {
+            let server = servers.get_mut(name)?;
+            Some(matches!(server.child.try_wait(), Ok(None)))
+        })
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerDetails {
+    pub running: bool,
+    // 本地 server 的 initialize 结果，远程 server 为 None
+    pub handshake: Option<ServerHandshake>,
 }
 
 #[tauri::command]
-pub async fn start_server(app_handle: AppHandle, name: String) -> Result<(), String> {
+pub fn get_server_details(name: &str) -> ServerDetails {
+    let running = get_server_status(name);
+    let handshake = RUNNING_SERVERS
+        .lock()
+        .ok()
+        .and_then(|servers| servers.get(name).map(|server| server.handshake.clone()));
+    ServerDetails { running, handshake }
+}
+
+// 正在运行的本地 server 的 JSON-RPC 会话
+pub fn server_session(name: &str) -> Result<Arc<McpSession>, AppError> {
+    RUNNING_SERVERS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock servers: {}", e)))?
+        .get(name)
+        .map(|server| server.session.clone())
+        .ok_or_else(|| {
+            AppError::localized(
+                ErrorCode::ServerNotRunning,
+                Message::ServerNotRunning,
+                &[("server", name)],
+            )
+            .with("server", name)
+        })
+}
+
+// 读取配置中的 server，并展开变量和密钥引用
+pub fn resolved_server_entry<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+) -> Result<McpServerEntry, AppError> {
     let config = crate::claude_config::get_claude_config()?;
     let server_config = config
         .mcp_servers
-        .get(&name)
-        .ok_or_else(|| format!("Server {} not found", name))?;
-
-    // 从 HashMap 中获取命令
-    let command = server_config
-        .get("command")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "Command not found in config".to_string())?;
-
-    // 从 HashMap 中获取参数数组
-    let args = server_config
-        .get("args")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| "Args not found in config".to_string())?
-        .iter()
-        .filter_map(|v| v.as_str().map(String::from))
-        .collect::<Vec<String>>();
-
-    println!("Starting server: {} with command: {}", name, command,);
+        .get(name)
+        .ok_or_else(|| AppError::server_not_found(name))?;
+    crate::variables::resolve_entry(app, server_config)
+}
+
+// 与 start_server 使用相同的启动参数，远程 server 没有启动参数
+#[tauri::command]
+pub fn get_server_launch_spec(app: AppHandle, name: String) -> Result<LaunchSpec, AppError> {
+    match resolved_server_entry(&app, &name)? {
+        McpServerEntry::Stdio(stdio) => Ok(LaunchSpec::new(
+            &name,
+            &stdio,
+            crate::store::load_env_inherit_policy(&app)?,
+        )),
+        McpServerEntry::Remote(_) => Err(AppError::localized(
+            ErrorCode::ServerIsRemote,
+            Message::ServerIsRemote,
+            &[("server", &name)],
+        )
+        .with("server", name.as_str())),
+    }
+}
+
+#[tauri::command]
+pub fn get_env_inherit_policy(app: AppHandle) -> Result<EnvInheritPolicy, AppError> {
+    crate::store::load_env_inherit_policy(&app)
+}
+
+#[tauri::command]
+pub fn set_env_inherit_policy(app: AppHandle, policy: EnvInheritPolicy) -> Result<(), AppError> {
+    crate::store::save_env_inherit_policy(&app, policy)
+}
+
+#[tauri::command]
+pub fn get_handshake_timeout(app: AppHandle) -> Result<u64, AppError> {
+    crate::store::load_handshake_timeout(&app)
+}
+
+#[tauri::command]
+pub fn set_handshake_timeout(app: AppHandle, seconds: u64) -> Result<(), AppError> {
+    crate::store::save_handshake_timeout(&app, seconds.max(1))
+}
+
+// 按当前设置启动本地 server，会阻塞到握手完成
+pub fn start_stdio_server<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    stdio: &StdioServerConfig,
+) -> Result<(), AppError> {
+    let spec = LaunchSpec::new(name, stdio, crate::store::load_env_inherit_policy(app)?);
+    let timeout = Duration::from_secs(crate::store::load_handshake_timeout(app)?);
+    start_mcp_server(&spec, timeout)
+}
+
+#[tauri::command]
+pub async fn start_server(app_handle: AppHandle, name: String) -> Result<(), AppError> {
+    // 启动时才展开变量和替换密钥引用
+    let server_config = resolved_server_entry(&app_handle, &name)?;
+
+    println!(
+        "Starting server: {} ({})",
+        name,
+        server_config.transport_name()
+    );
 
     // 启动服务器
-    start_mcp_server(&name, command, &args, None)
-        .and_then(|()| update_tray_status(app_handle).map_err(|e| e.to_string()))
+    match server_config {
+        McpServerEntry::Stdio(stdio) => {
+            // 握手可能要等待较长时间，不占用异步运行时的线程
+            let app = app_handle.clone();
+            let server_name = name.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                start_stdio_server(&app, &server_name, &stdio)
+            })
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to start server: {}", e)))??;
+        }
+        McpServerEntry::Remote(remote) => {
+            connect_remote_server(&name, &remote).await?;
+        }
+    }
+
+    update_tray_status(app_handle).map_err(|e| AppError::internal(e.to_string()))
 }
 
 #[tauri::command]
-pub async fn stop_server(app_handle: AppHandle, name: String) -> Result<(), String> {
-    stop_mcp_server(&name).and_then(|()| update_tray_status(app_handle).map_err(|e| e.to_string()))
+pub async fn stop_server(app_handle: AppHandle, name: String) -> Result<(), AppError> {
+    stop_mcp_server(&name)?;
+    update_tray_status(app_handle).map_err(|e| AppError::internal(e.to_string()))
 }
 
 #[tauri::command]
-pub fn restart_claude_app() -> Result<(), String> {
+pub fn restart_claude_app() -> Result<(), AppError> {
+    let process_failed = |message: Message| {
+        AppError::localized(ErrorCode::ProcessFailed, message, &[]).with("process", "Claude")
+    };
+
     // 先终止 Claude 进程
     let kill_status = Command::new("killall")
         .arg("Claude")
         .status()
-        .map_err(|e| format!("Failed to kill Claude: {}", e))?;
+        .map_err(|e| process_failed(Message::ClaudeKillFailed).with("error", e.to_string()))?;
 
     if !kill_status.success() {
-        return Err("Failed to kill Claude process".to_string());
+        return Err(process_failed(Message::ClaudeKillFailed));
     }
 
     // 短暂延迟确保进程完全终止
@@ -238,10 +589,10 @@ pub fn restart_claude_app() -> Result<(), String> {
         .arg("-a")
         .arg("Claude")
         .status()
-        .map_err(|e| format!("Failed to start Claude: {}", e))?;
+        .map_err(|e| process_failed(Message::ClaudeStartFailed).with("error", e.to_string()))?;
 
     if !open_status.success() {
-        return Err("Failed to start Claude".to_string());
+        return Err(process_failed(Message::ClaudeStartFailed));
     }
 
     Ok(())
@@ -1,12 +1,20 @@
-use crate::store::InstalledMcpServer;
-use reqwest;
+use crate::config_validation::{ensure_valid, validate_server_entry};
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::journal::{self, JournalServerChange, JournalStoreChanges};
+use crate::mcp_clients::{
+    change_client_config, get_server_clients, read_client_config, server_config_error,
+    target_clients, ConfigPreview, McpClient,
+};
+use crate::server_entry::{McpServerEntry, RemoteTransport};
+use crate::store::{DisabledMcpServer, InstalledMcpServer};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::mpsc;
 use tauri::AppHandle;
 use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_fs::FsExt;
-use serde_json::Value as JsonValue;
 
 // 新的数据结构匹配 API 返回
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,9 +35,18 @@ pub struct McpServerTemplate {
     pub id: String,
     pub name: String,
     pub description: String,
+    #[serde(default)]
     pub command: String,
+    #[serde(default)]
     pub args: Vec<String>,
     pub env: Option<HashMap<String, String>>,
+    // 远程 server 模板只提供 url
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(rename = "type", default)]
+    pub transport: Option<RemoteTransport>,
+    #[serde(default)]
+    pub headers: Option<HashMap<String, String>>,
     pub require_file_path: bool,
     pub repo_url: String,
     pub created_at: String,
@@ -38,6 +55,30 @@ pub struct McpServerTemplate {
     pub total_usage_time: i32,
 }
 
+impl McpServerTemplate {
+    // 根据模板生成写入配置文件的 server 配置
+    pub fn to_entry(&self) -> McpServerEntry {
+        if let Some(url) = &self.url {
+            let headers = self
+                .headers
+                .as_ref()
+                .filter(|headers| !headers.is_empty())
+                .map(|headers| headers.clone().into_iter().collect::<IndexMap<_, _>>());
+            return McpServerEntry::remote(url.clone(), self.transport, headers);
+        }
+
+        // 只在 env 存在且不为空时才添加
+        let env = self
+            .env
+            .as_ref()
+            .filter(|env| !env.is_empty())
+            .map(|env| env.clone().into_iter().collect::<IndexMap<_, _>>());
+        McpServerEntry::stdio(self.command.clone(), self.args.clone(), env)
+    }
+}
+
+const TEMPLATES_URL: &str = "http://127.0.0.1:3988/api/v1/templates";
+
 // 分页参数结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaginationParams {
@@ -49,7 +90,7 @@ pub struct PaginationParams {
 
 // 修改获取模板的函数
 #[tauri::command]
-pub async fn get_mcp_server_templates(params: PaginationParams) -> Result<ApiData, String> {
+pub async fn get_mcp_server_templates(params: PaginationParams) -> Result<ApiData, AppError> {
     println!("Fetching templates with params: {:?}", params);
 
     let client = reqwest::Client::new();
@@ -72,13 +113,18 @@ pub async fn get_mcp_server_templates(params: PaginationParams) -> Result<ApiDat
 
     // 发送请求
     let response = client
-        .get("http://127.0.0.1:3988/api/v1/templates")
+        .get(TEMPLATES_URL)
         .query(&query)
         .send()
         .await
         .map_err(|e| {
             println!("Request failed: {}", e);
-            format!("Failed to fetch templates: {}", e)
+            AppError::localized(
+                ErrorCode::Network,
+                Message::TemplatesFetchFailed,
+                &[("error", &e.to_string())],
+            )
+            .with("url", TEMPLATES_URL)
         })?;
 
     println!("Received response status: {}", response.status());
@@ -86,106 +132,366 @@ pub async fn get_mcp_server_templates(params: PaginationParams) -> Result<ApiDat
     // 解析响应
     let api_response: ApiResponse = response.json().await.map_err(|e| {
         println!("Failed to parse response: {}", e);
-        format!("Failed to parse response: {}", e)
+        AppError::localized(
+            ErrorCode::Api,
+            Message::TemplatesParseFailed,
+            &[("error", &e.to_string())],
+        )
+        .with("url", TEMPLATES_URL)
     })?;
 
     // println!("Parsed response: {:?}", api_response);
 
     if api_response.code != 0 {
         println!("API error: {}", api_response.msg);
-        return Err(api_response.msg);
+        return Err(AppError::new(ErrorCode::Api, api_response.msg)
+            .with("url", TEMPLATES_URL)
+            .with("api_code", api_response.code));
     }
 
     Ok(api_response.data)
 }
 
-#[tauri::command]
-pub async fn install_mcp_server(app: AppHandle, template: McpServerTemplate) -> Result<(), String> {
-    // 在修改之前创建备份
-    crate::claude_config::backup_config()?;
-
-    // 保存到 store
-    crate::store::save_installed_server(
-        app.clone(),
-        InstalledMcpServer {
-            id: template.id.clone(),
-            name: template.name.clone(),
-            command: template.command.clone(),
-            args: template.args.clone(),
-            env: template.env.clone(),
-            require_file_path: template.require_file_path,
-            repo_url: template.repo_url.clone(),
-        },
+// 对每个目标客户端执行操作，返回成功的结果，并汇总失败的客户端；
+// 错误码和 context 取第一个失败的客户端
+fn apply_to_clients<T>(
+    clients: &[McpClient],
+    mut f: impl FnMut(McpClient) -> Result<T, AppError>,
+) -> (Vec<T>, Result<(), AppError>) {
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    for &client in clients {
+        match f(client) {
+            Ok(result) => results.push(result),
+            Err(e) => failures.push((client, e)),
+        }
+    }
+
+    let Some((_, first)) = failures.first() else {
+        return (results, Ok(()));
+    };
+    let message = failures
+        .iter()
+        .map(|(client, e)| format!("{}: {}", client.display_name(), e))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let failed_clients: Vec<McpClient> = failures.iter().map(|(client, _)| *client).collect();
+    let mut error = AppError::new(first.code, message);
+    error.context = first.context.clone();
+    (
+        results,
+        Err(error.with("clients", serde_json::json!(failed_clients))),
     )
-    .await?;
+}
 
-    // 获取当前配置
-    let mut claude_config = crate::claude_config::get_claude_config()?;
+// 将 server 写入各个客户端的配置，并记录到 store；dry_run 时只返回预览
+pub fn install_server(
+    app: &AppHandle,
+    server: InstalledMcpServer,
+    clients: &[McpClient],
+    dry_run: bool,
+) -> Result<Vec<ConfigPreview>, AppError> {
+    // 配置中可能有密钥，只打印名称和传输方式
+    println!(
+        "Installing server {} ({})",
+        server.name,
+        server.entry.transport_name()
+    );
+    let previews = install_servers(app, vec![server], clients, dry_run)?;
+    println!("Server installed successfully");
+    Ok(previews)
+}
 
-    // 创建新的服务器配置
-    let mut server_config = HashMap::new();
-    server_config.insert("command".to_string(), serde_json::json!(template.command));
-    server_config.insert("args".to_string(), serde_json::json!(template.args));
-    
-    // 只在 env 存在且不为空时才添加
-    if let Some(env) = template.env {
-        if !env.is_empty() {
-            server_config.insert("env".to_string(), serde_json::json!(env));
+// 一次安装多个 server：每个客户端的配置只读写一次，其中任何一个 server 无效时
+// 该客户端不做任何修改
+pub fn install_servers(
+    app: &AppHandle,
+    mut servers: Vec<InstalledMcpServer>,
+    clients: &[McpClient],
+    dry_run: bool,
+) -> Result<Vec<ConfigPreview>, AppError> {
+    // 安装多个 server 时在错误信息中指明是哪一个
+    let single = servers.len() == 1;
+    let named = |name: &str, e: AppError| {
+        if single {
+            e
+        } else {
+            server_config_error(name, e)
         }
+    };
+
+    // 写入客户端配置的是展开变量、替换密钥引用后的配置，store 中保留引用
+    let entries = servers
+        .iter()
+        .map(|server| {
+            crate::variables::resolve_entry(app, &server.entry).map_err(|e| named(&server.name, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // 更新每个客户端的配置
+    let (previews, result) = apply_to_clients(clients, |client| {
+        change_client_config(client, dry_run, |config| {
+            for (server, entry) in servers.iter().zip(&entries) {
+                ensure_valid(&validate_server_entry(
+                    client.servers_key(),
+                    &server.name,
+                    entry,
+                    config.server_names().iter(),
+                ))
+                .and_then(|()| config.set_server(&server.name, entry))
+                .map_err(|e| named(&server.name, e))?;
+            }
+            Ok(())
+        })
+    });
+
+    if dry_run {
+        return result.map(|()| previews);
     }
 
-    println!("Installing server with config: {:?}", &server_config);
-    
-    // 更新配置
-    claude_config.mcp_servers.insert(template.name, server_config);
+    // 至少装进了一个目标客户端时才记录到 store
+    let mut server_changes = Vec::new();
+    if !previews.is_empty() {
+        crate::store::update_installed_servers(app, |installed| {
+            for server in &mut servers {
+                // 重新安装时保留重启策略和崩溃记录
+                if let Some(existing) = installed.get(&server.name) {
+                    server.restart_policy = existing.restart_policy.clone();
+                    server.crashes = existing.crashes.clone();
+                }
+                server_changes.push(JournalServerChange::new(
+                    &server.name,
+                    installed.get(&server.name),
+                    Some(server),
+                ));
+                installed.insert(server.name.clone(), server.clone());
+            }
+            Ok(())
+        })?;
+    }
 
-    // 保存配置
-    crate::claude_config::save_claude_config(claude_config)?;
+    let names: Vec<&str> = servers.iter().map(|server| server.name.as_str()).collect();
+    journal::record(
+        app,
+        format!("Install {}", names.join(", ")),
+        &previews,
+        JournalStoreChanges::servers(server_changes),
+    );
 
-    println!("Server installed successfully");
+    result?;
+    Ok(previews)
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn install_mcp_server(
+    app: AppHandle,
+    template: McpServerTemplate,
+    clients: Option<Vec<McpClient>>,
+    dry_run: Option<bool>,
+) -> Result<Vec<ConfigPreview>, AppError> {
+    // 创建新的服务器配置
+    let server = InstalledMcpServer {
+        id: template.id.clone(),
+        name: template.name.clone(),
+        entry: template.to_entry(),
+        require_file_path: template.require_file_path,
+        repo_url: template.repo_url.clone(),
+        restart_policy: Default::default(),
+        crashes: Vec::new(),
+    };
+
+    install_server(
+        &app,
+        server,
+        &target_clients(clients),
+        dry_run.unwrap_or(false),
+    )
 }
 
 #[tauri::command]
-pub fn is_mcp_server_installed(name: String) -> Result<bool, String> {
-    let config = crate::claude_config::get_claude_config()?;
-    Ok(config.mcp_servers.contains_key(&name))
+pub fn is_mcp_server_installed(name: String, client: Option<McpClient>) -> Result<bool, AppError> {
+    let config = read_client_config(client.unwrap_or(McpClient::ClaudeDesktop))?;
+    Ok(config.contains_server(&name))
 }
 
 #[tauri::command]
-pub async fn uninstall_mcp_server(app: AppHandle, name: String) -> Result<(), String> {
-    // 在修改之前创建备份
-    crate::claude_config::backup_config()?;
+pub async fn uninstall_mcp_server(
+    app: AppHandle,
+    name: String,
+    clients: Option<Vec<McpClient>>,
+    dry_run: Option<bool>,
+) -> Result<Vec<ConfigPreview>, AppError> {
+    let clients = target_clients(clients);
+    let dry_run = dry_run.unwrap_or(false);
 
     // 停止运行中的 server
-    crate::mcp_runner::stop_mcp_server(&name)?;
+    if !dry_run && clients.contains(&McpClient::ClaudeDesktop) {
+        crate::mcp_runner::stop_mcp_server(&name)?;
+    }
+
+    // 被禁用的 server 也一并删除
+    let disabled_before = crate::store::load_disabled_servers(&app)?;
+    let mut disabled = disabled_before.clone();
+    disabled.retain(|server| !(server.name == name && clients.contains(&server.client)));
+    let was_disabled = disabled.len() != disabled_before.len();
+    if was_disabled && !dry_run {
+        crate::store::save_disabled_servers(&app, &disabled)?;
+    }
+
+    let (previews, result) = apply_to_clients(&clients, |client| {
+        change_client_config(client, dry_run, |config| {
+            // 删除服务器
+            match config.remove_server(&name) {
+                Some(_) => Ok(()),
+                None if was_disabled => Ok(()),
+                None => Err(AppError::server_not_found(&name)),
+            }
+        })
+    });
 
-    let mut config = crate::claude_config::get_claude_config()?;
+    if dry_run {
+        return result.map(|()| previews);
+    }
 
-    // 检查服务器是否存在
-    if !config.mcp_servers.contains_key(&name) {
-        return Err(format!("MCP server '{}' not found", name));
+    // 所有客户端都不再使用时，从 store 中删除服务器配置
+    let mut server_changes = Vec::new();
+    let still_disabled = disabled.iter().any(|server| server.name == name);
+    if !still_disabled && get_server_clients(name.clone()).is_empty() {
+        crate::store::update_installed_servers(&app, |installed| {
+            if let Some(server) = installed.remove(&name) {
+                server_changes.push(JournalServerChange::new(&name, Some(&server), None));
+            }
+            Ok(())
+        })?;
     }
 
-    // 删除服务器
-    config.mcp_servers.remove(&name);
+    journal::record(
+        &app,
+        format!("Uninstall {}", name),
+        &previews,
+        JournalStoreChanges::servers(server_changes).with_disabled(disabled_before, &disabled),
+    );
+
+    result.map(|()| previews)
+}
+
+#[tauri::command]
+pub fn disable_mcp_server(
+    app: AppHandle,
+    name: String,
+    client: Option<McpClient>,
+) -> Result<(), AppError> {
+    let client = client.unwrap_or(McpClient::ClaudeDesktop);
+
+    let disabled_before = crate::store::load_disabled_servers(&app)?;
+    let mut disabled = disabled_before.clone();
+    if disabled
+        .iter()
+        .any(|server| server.name == name && server.client == client)
+    {
+        return Err(AppError::localized(
+            ErrorCode::ServerAlreadyDisabled,
+            Message::ServerAlreadyDisabled,
+            &[("server", &name)],
+        )
+        .with("server", name.as_str()));
+    }
 
-    // 保存更新后的配置
-    if let Err(e) = crate::claude_config::save_claude_config(config) {
-        // 如果保存失败，尝试恢复备份
-        crate::claude_config::restore_config_backup()?;
-        return Err(e);
+    if client == McpClient::ClaudeDesktop {
+        crate::mcp_runner::stop_mcp_server(&name)?;
     }
 
-    // 从 store 中删除服务器配置
-    crate::store::remove_installed_server(app, name).await?;
+    let installed = crate::store::load_installed_servers(&app)?;
+    let preview = change_client_config(client, false, |config| {
+        let position = config
+            .server_position(&name)
+            .ok_or_else(|| AppError::server_not_found(&name))?;
+        let mut value = config
+            .remove_server(&name)
+            .ok_or_else(|| AppError::server_not_found(&name))?;
+        if let Some(server) = installed.get(&name) {
+            let template = serde_json::to_value(&server.entry)
+                .map_err(|e| AppError::internal(format!("Failed to serialize server: {}", e)))?;
+            crate::variables::restore_references(&app, &template, &mut value)?;
+        }
+
+        // 先保存到 store 再写配置文件，写入失败时 server 仍保留在配置中
+        disabled.push(DisabledMcpServer {
+            name: name.clone(),
+            client,
+            config: value,
+            position,
+            disabled_at: chrono::Local::now().to_rfc3339(),
+        });
+        crate::store::save_disabled_servers(&app, &disabled)
+    })?;
+
+    journal::record(
+        &app,
+        format!("Disable {}", name),
+        &[preview],
+        JournalStoreChanges::default().with_disabled(disabled_before, &disabled),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn enable_mcp_server(
+    app: AppHandle,
+    name: String,
+    client: Option<McpClient>,
+) -> Result<(), AppError> {
+    let client = client.unwrap_or(McpClient::ClaudeDesktop);
+
+    let disabled_before = crate::store::load_disabled_servers(&app)?;
+    let mut disabled = disabled_before.clone();
+    let index = disabled
+        .iter()
+        .position(|server| server.name == name && server.client == client)
+        .ok_or_else(|| {
+            AppError::localized(
+                ErrorCode::ServerNotDisabled,
+                Message::ServerNotDisabled,
+                &[("server", &name)],
+            )
+            .with("server", name.as_str())
+        })?;
+    let mut server = disabled.remove(index);
+    crate::variables::resolve_value(&app, &mut server.config)?;
+
+    let preview = change_client_config(client, false, |config| {
+        // 配置中已有同名 server 时说明之前禁用未完成，直接丢弃保存的副本
+        if config.contains_server(&name) {
+            return Ok(());
+        }
+        config.insert_server_value(&name, server.position, server.config)
+    })?;
 
+    crate::store::save_disabled_servers(&app, &disabled)?;
+    journal::record(
+        &app,
+        format!("Enable {}", name),
+        &[preview],
+        JournalStoreChanges::default().with_disabled(disabled_before, &disabled),
+    );
     Ok(())
 }
 
 #[tauri::command]
-pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
+pub fn list_disabled_servers(
+    app: AppHandle,
+    client: Option<McpClient>,
+) -> Result<Vec<DisabledMcpServer>, AppError> {
+    let disabled = crate::store::load_disabled_servers(&app)?;
+
+    Ok(disabled
+        .into_iter()
+        .filter(|server| client.is_none_or(|client| server.client == client))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
     let (tx, rx) = mpsc::channel();
 
     app.dialog().file().pick_folders(move |folders| {
@@ -202,12 +508,14 @@ pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, Stri
 
     let result = rx
         .recv()
-        .map_err(|e| format!("Failed to receive result: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to receive result: {}", e)))?;
 
     if let Some(path) = &result {
         println!("Allowing directory access: {}", path);
         let fs = app.fs_scope();
-        fs.allow_directory(path, true);
+        if let Err(e) = fs.allow_directory(path, true) {
+            eprintln!("Failed to allow directory access: {}", e);
+        }
     }
 
     Ok(result)
@@ -215,23 +523,55 @@ pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, Stri
 
 #[tauri::command]
 pub fn update_mcp_server_config(
-    name: String, 
-    config: HashMap<String, JsonValue>
-) -> Result<(), String> {
-    // 在修改之前创建备份
-    crate::claude_config::backup_config()?;
-
-    let mut claude_config = crate::claude_config::get_claude_config()?;
-
-    // 更新配置
-    claude_config.mcp_servers.insert(name, config);
-
-    // 保存更新后的配置
-    if let Err(e) = crate::claude_config::save_claude_config(claude_config) {
-        // 如果保存失败，尝试恢复备份
-        crate::claude_config::restore_config_backup()?;
-        return Err(e);
+    app: AppHandle,
+    name: String,
+    config: McpServerEntry,
+    client: Option<McpClient>,
+    dry_run: Option<bool>,
+) -> Result<ConfigPreview, AppError> {
+    let dry_run = dry_run.unwrap_or(false);
+    // 客户端配置中写入展开后的值，store 中保留引用
+    let resolved = crate::variables::resolve_entry(&app, &config)?;
+    let preview = change_client_config(
+        client.unwrap_or(McpClient::ClaudeDesktop),
+        dry_run,
+        |client_config| {
+            ensure_valid(&validate_server_entry(
+                client_config.client.servers_key(),
+                &name,
+                &resolved,
+                client_config.server_names().iter(),
+            ))?;
+
+            // 更新配置
+            client_config.set_server(&name, &resolved)
+        },
+    )?;
+
+    if dry_run {
+        return Ok(preview);
     }
 
-    Ok(())
+    // 同步到 store，切换 profile 时以最新的配置为准
+    let mut server_changes = Vec::new();
+    crate::store::update_installed_servers(&app, |installed| {
+        if let Some(server) = installed.get_mut(&name) {
+            let before = server.clone();
+            let mut entry = config;
+            // 未修改的变量和密钥保持引用，不以展开后的值写入 store
+            crate::variables::restore_entry_references(&app, &server.entry, &mut entry)?;
+            server.entry = entry;
+            server_changes.push(JournalServerChange::new(&name, Some(&before), Some(server)));
+        }
+        Ok(())
+    })?;
+
+    journal::record(
+        &app,
+        format!("Update {}", name),
+        std::slice::from_ref(&preview),
+        JournalStoreChanges::servers(server_changes),
+    );
+
+    Ok(preview)
 }
@@ -0,0 +1,248 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::ChildStdin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+// 发送 initialize 时使用的协议版本，server 可以回复它支持的其它版本
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+// JSON-RPC 规定的 "Method not found" 错误码
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// initialize 的结果，作为 server 已经可用的依据
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerHandshake {
+    pub protocol_version: String,
+    // server 返回的 serverInfo，至少包含 name 和 version
+    pub server_info: JsonValue,
+    pub capabilities: JsonValue,
+    pub instructions: Option<String>,
+    pub initialized_at: String,
+}
+
+type PendingResponse = Result<JsonValue, AppError>;
+
+// 通过子进程的 stdin/stdout 与 server 通信的 JSON-RPC 会话
+pub struct McpSession {
+    server: String,
+    stdin: Mutex<ChildStdin>,
+    // 等待响应的请求，stdout 关闭后清空，等待方随即收到错误
+    pending: Mutex<Option<HashMap<u64, mpsc::Sender<PendingResponse>>>>,
+    next_id: AtomicU64,
+}
+
+impl McpSession {
+    pub fn new(server: &str, stdin: ChildStdin) -> Self {
+        McpSession {
+            server: server.to_string(),
+            stdin: Mutex::new(stdin),
+            pending: Mutex::new(Some(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn closed_error(&self) -> AppError {
+        AppError::localized(
+            ErrorCode::ServerNotRunning,
+            Message::ServerNotRunning,
+            &[("server", &self.server)],
+        )
+        .with("server", self.server.as_str())
+    }
+
+    fn send(&self, message: &JsonValue) -> Result<(), AppError> {
+        let mut stdin = self
+            .stdin
+            .lock()
+            .map_err(|e| AppError::internal(format!("Failed to lock stdin: {}", e)))?;
+        // 每条消息占一行，消息内部不能有换行
+        writeln!(stdin, "{}", message)
+            .and_then(|_| stdin.flush())
+            .map_err(|_| self.closed_error())
+    }
+
+    // 处理 stdout 中的一行，无法解析的内容只保留在日志里
+    pub fn handle_line(&self, line: &str) {
+        let Ok(JsonValue::Object(message)) = serde_json::from_str::<JsonValue>(line) else {
+            return;
+        };
+
+        match (message.get("id"), message.get("method")) {
+            // 对我们发出的请求的响应
+            (Some(id), None) => {
+                let Some(id) = id.as_u64() else {
+                    return;
+                };
+                let sender = self
+                    .pending
+                    .lock()
+                    .ok()
+                    .and_then(|mut pending| pending.as_mut()?.remove(&id));
+                let Some(sender) = sender else {
+                    return;
+                };
+
+                let response = match (message.get("result"), message.get("error")) {
+                    (_, Some(error)) => Err(self.rpc_error(error)),
+                    (Some(result), None) => Ok(result.clone()),
+                    (None, None) => Err(self.invalid_response("missing result")),
+                };
+                let _ = sender.send(response);
+            }
+            // server 发来的请求，只支持 ping
+            (Some(id), Some(method)) => {
+                let reply = if method == "ping" {
+                    json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+                } else {
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": METHOD_NOT_FOUND, "message": "Method not found" },
+                    })
+                };
+                if let Err(e) = self.send(&reply) {
+                    eprintln!("Failed to reply to {}: {}", self.server, e);
+                }
+            }
+            // 通知不需要回复
+            (None, _) => {}
+        }
+    }
+
+    // stdout 已关闭，不会再有响应
+    pub fn close(&self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.take();
+        }
+    }
+
+    fn rpc_error(&self, error: &JsonValue) -> AppError {
+        let message = error
+            .get("message")
+            .and_then(|message| message.as_str())
+            .unwrap_or("Unknown error");
+        let mut app_error = AppError::localized(
+            ErrorCode::RpcError,
+            Message::RpcError,
+            &[("error", message)],
+        )
+        .with("server", self.server.as_str());
+        if let Some(code) = error.get("code") {
+            app_error = app_error.with("rpc_code", code.clone());
+        }
+        if let Some(data) = error.get("data") {
+            app_error = app_error.with("data", data.clone());
+        }
+        app_error
+    }
+
+    fn invalid_response(&self, error: &str) -> AppError {
+        AppError::localized(
+            ErrorCode::RpcError,
+            Message::InvalidRpcResponse,
+            &[("error", error)],
+        )
+        .with("server", self.server.as_str())
+    }
+
+    // 发送请求并阻塞等待响应
+    pub fn request(
+        &self,
+        method: &str,
+        params: JsonValue,
+        timeout: Duration,
+    ) -> Result<JsonValue, AppError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel();
+        self.pending
+            .lock()
+            .map_err(|e| AppError::internal(format!("Failed to lock requests: {}", e)))?
+            .as_mut()
+            .ok_or_else(|| self.closed_error())?
+            .insert(id, sender);
+
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(e) = self.send(&request) {
+            self.forget(id);
+            return Err(e);
+        }
+
+        match receiver.recv_timeout(timeout) {
+            Ok(response) => response,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                self.forget(id);
+                Err(AppError::localized(
+                    ErrorCode::RequestTimeout,
+                    Message::RequestTimeout,
+                    &[
+                        ("method", method),
+                        ("seconds", &timeout.as_secs().to_string()),
+                    ],
+                )
+                .with("server", self.server.as_str())
+                .with("method", method))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(self.closed_error()),
+        }
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            if let Some(pending) = pending.as_mut() {
+                pending.remove(&id);
+            }
+        }
+    }
+
+    pub fn notify(&self, method: &str, params: JsonValue) -> Result<(), AppError> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    // 完成 initialize 握手，返回 server 的信息和能力
+    pub fn initialize(&self, timeout: Duration) -> Result<ServerHandshake, AppError> {
+        let result = self.request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "mcp-manager",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            }),
+            timeout,
+        )?;
+
+        let protocol_version = result
+            .get("protocolVersion")
+            .and_then(|version| version.as_str())
+            .ok_or_else(|| self.invalid_response("missing protocolVersion"))?;
+        let server_info = result
+            .get("serverInfo")
+            .filter(|info| info.is_object())
+            .ok_or_else(|| self.invalid_response("missing serverInfo"))?;
+        let capabilities = result
+            .get("capabilities")
+            .filter(|capabilities| capabilities.is_object())
+            .ok_or_else(|| self.invalid_response("missing capabilities"))?;
+
+        self.notify("notifications/initialized", json!({}))?;
+
+        Ok(ServerHandshake {
+            protocol_version: protocol_version.to_string(),
+            server_info: server_info.clone(),
+            capabilities: capabilities.clone(),
+            instructions: result
+                .get("instructions")
+                .and_then(|instructions| instructions.as_str())
+                .map(|instructions| instructions.to_string()),
+            initialized_at: chrono::Local::now().to_rfc3339(),
+        })
+    }
+}
@@ -0,0 +1,218 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::journal::JournalStoreChanges;
+use crate::mcp_clients::{change_client_config, McpClient};
+use crate::server_entry::McpServerEntry;
+use crate::store;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+// 在 profile 中对单个 server 做的覆盖，未设置的字段沿用安装时的配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<IndexMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<IndexMap<String, String>>,
+}
+
+impl ServerOverride {
+    fn apply(&self, entry: &mut McpServerEntry) {
+        match entry {
+            McpServerEntry::Stdio(stdio) => {
+                if let Some(args) = &self.args {
+                    stdio.args = args.clone();
+                }
+                // env 按键合并，覆盖同名变量
+                if let Some(env) = &self.env {
+                    stdio
+                        .env
+                        .get_or_insert_with(IndexMap::new)
+                        .extend(env.clone());
+                }
+            }
+            McpServerEntry::Remote(remote) => {
+                if let Some(headers) = &self.headers {
+                    remote
+                        .headers
+                        .get_or_insert_with(IndexMap::new)
+                        .extend(headers.clone());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerProfile {
+    pub name: String,
+    // 启用的已安装 server，按写入配置的顺序排列
+    pub servers: Vec<String>,
+    #[serde(default)]
+    pub overrides: IndexMap<String, ServerOverride>,
+}
+
+fn profile_not_found(name: &str) -> AppError {
+    AppError::localized(
+        ErrorCode::ProfileNotFound,
+        Message::ProfileNotFound,
+        &[("profile", name)],
+    )
+    .with("profile", name)
+}
+
+// 应用当前 profile 对该 server 的覆盖后的配置，没有启用 profile 时原样返回
+pub fn active_entry<R: Runtime>(
+    app: &AppHandle<R>,
+    server: &str,
+    entry: &McpServerEntry,
+) -> Result<McpServerEntry, AppError> {
+    let active = store::load_active_profile(app)?;
+    profile_entry(app, active.as_deref(), server, entry)
+}
+
+// 应用指定 profile 对该 server 的覆盖后的配置
+pub fn profile_entry<R: Runtime>(
+    app: &AppHandle<R>,
+    profile: Option<&str>,
+    server: &str,
+    entry: &McpServerEntry,
+) -> Result<McpServerEntry, AppError> {
+    let mut entry = entry.clone();
+    let Some(profile) = profile else {
+        return Ok(entry);
+    };
+    if let Some(server_override) = store::load_profiles(app)?
+        .get(profile)
+        .and_then(|profile| profile.overrides.get(server))
+    {
+        server_override.apply(&mut entry);
+    }
+    Ok(entry)
+}
+
+// 切换到指定 profile：启用其中的 server，移除其它由本程序管理的 server
+pub fn apply_profile<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    client: McpClient,
+) -> Result<(), AppError> {
+    let profiles = store::load_profiles(app)?;
+    let profile = profiles.get(name).ok_or_else(|| profile_not_found(name))?;
+    let installed = store::load_installed_servers(app)?;
+
+    let missing: Vec<&str> = profile
+        .servers
+        .iter()
+        .filter(|server| !installed.contains_key(*server))
+        .map(|server| server.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::ProfileInvalid,
+            Message::ProfileServersNotInstalled,
+            &[("profile", name), ("servers", &missing.join(", "))],
+        )
+        .with("profile", name)
+        .with("servers", serde_json::json!(missing)));
+    }
+
+    let preview = change_client_config::<AppError>(client, false, |config| {
+        // 不在 store 中的 server 是用户手动添加的，保持不动
+        for server in installed.keys() {
+            if !profile.servers.contains(server) {
+                config.remove_server(server);
+            }
+        }
+
+        for server in &profile.servers {
+            let mut entry = installed[server].entry.clone();
+            if let Some(server_override) = profile.overrides.get(server) {
+                server_override.apply(&mut entry);
+            }
+            config.set_server(server, &crate::variables::resolve_entry(app, &entry)?)?;
+        }
+
+        Ok(())
+    })?;
+
+    // profile 启用的 server 不再处于禁用状态
+    let disabled_before = store::load_disabled_servers(app)?;
+    let mut disabled = disabled_before.clone();
+    disabled.retain(|server| !(server.client == client && profile.servers.contains(&server.name)));
+    if disabled.len() != disabled_before.len() {
+        store::save_disabled_servers(app, &disabled)?;
+    }
+
+    let active_before = store::load_active_profile(app)?;
+    store::save_active_profile(app, Some(name))?;
+
+    crate::journal::record(
+        app,
+        format!("Switch to profile {}", name),
+        &[preview],
+        JournalStoreChanges::default()
+            .with_disabled(disabled_before, &disabled)
+            .with_active_profile(active_before, Some(name)),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<Vec<ServerProfile>, AppError> {
+    Ok(store::load_profiles(&app)?.into_values().collect())
+}
+
+#[tauri::command]
+pub fn save_profile(app: AppHandle, profile: ServerProfile) -> Result<(), AppError> {
+    if profile.name.trim().is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::ProfileInvalid,
+            Message::ProfileNameEmpty,
+            &[],
+        ));
+    }
+
+    let mut profiles = store::load_profiles(&app)?;
+    profiles.insert(profile.name.clone(), profile);
+    store::save_profiles(&app, &profiles)?;
+
+    let _ = crate::tray::update_tray_status(app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> Result<(), AppError> {
+    let mut profiles = store::load_profiles(&app)?;
+    if profiles.shift_remove(&name).is_none() {
+        return Err(profile_not_found(&name));
+    }
+    store::save_profiles(&app, &profiles)?;
+
+    if store::load_active_profile(&app)?.as_deref() == Some(name.as_str()) {
+        store::save_active_profile(&app, None)?;
+    }
+
+    let _ = crate::tray::update_tray_status(app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_active_profile(app: AppHandle) -> Result<Option<String>, AppError> {
+    store::load_active_profile(&app)
+}
+
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    name: String,
+    client: Option<McpClient>,
+) -> Result<(), AppError> {
+    apply_profile(&app, &name, client.unwrap_or(McpClient::ClaudeDesktop))?;
+
+    let _ = crate::tray::update_tray_status(app);
+    Ok(())
+}
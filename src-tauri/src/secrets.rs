@@ -0,0 +1,643 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::mcp_clients::{get_server_clients, modify_client_config, McpClient};
+use crate::server_entry::McpServerEntry;
+use crate::store;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use indexmap::IndexMap;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+// server 配置中引用密钥的写法：${secret:NAME}
+const REFERENCE_PREFIX: &str = "${secret:";
+const KEY_FILE: &str = "vault.key";
+// 轮换主密钥时先写入新密钥，store 更新完成后再替换
+const NEXT_KEY_FILE: &str = "vault.key.next";
+const KEY_LEN: usize = 32;
+
+// 加密后的密钥，value 为 base64(nonce + 密文)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSecret {
+    pub value: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretUsage {
+    pub server: String,
+    // 在 profile 的覆盖配置中引用时为 profile 名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    // 在禁用后保存的配置中引用时为所在的客户端
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<McpClient>,
+    pub pointer: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretInfo {
+    pub name: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub used_by: Vec<SecretUsage>,
+}
+
+fn key_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+    app.path().app_data_dir().map_err(|e| {
+        AppError::localized(
+            ErrorCode::Io,
+            Message::AppDataDirFailed,
+            &[("error", &e.to_string())],
+        )
+    })
+}
+
+fn read_key(path: &PathBuf) -> Result<Option<[u8; KEY_LEN]>, AppError> {
+    match fs::read(path) {
+        Ok(bytes) => bytes.try_into().map(Some).map_err(|_| {
+            AppError::localized(
+                ErrorCode::VaultKeyInvalid,
+                Message::VaultKeyInvalid,
+                &[("path", &path_value(path))],
+            )
+            .with("path", path_value(path))
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AppError::io(Message::VaultKeyReadFailed, path, e)),
+    }
+}
+
+fn write_key(path: &PathBuf, key: &[u8; KEY_LEN]) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io(Message::VaultKeyWriteFailed, parent, e))?;
+    }
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| AppError::internal(format!("Invalid vault key path: {}", path.display())))?;
+    let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    // 临时文件在创建时就只允许当前用户读写，重命名前也不会被其它用户读到
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let _ = fs::remove_file(&tmp_path);
+    let write_result = (|| {
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(key)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if let Err(e) = write_result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(AppError::io(Message::VaultKeyWriteFailed, path, e));
+    }
+    Ok(())
+}
+
+fn generate_key() -> Result<[u8; KEY_LEN], AppError> {
+    let mut key = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| AppError::internal("Failed to generate vault key"))?;
+    Ok(key)
+}
+
+// 当前主密钥，第一次使用时生成
+fn load_or_create_key<R: Runtime>(app: &AppHandle<R>) -> Result<[u8; KEY_LEN], AppError> {
+    let path = key_dir(app)?.join(KEY_FILE);
+    if let Some(key) = read_key(&path)? {
+        return Ok(key);
+    }
+
+    let key = generate_key()?;
+    write_key(&path, &key)?;
+    Ok(key)
+}
+
+// 解密时依次尝试的主密钥，包括未完成轮换留下的新密钥
+fn load_keys<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<[u8; KEY_LEN]>, AppError> {
+    let dir = key_dir(app)?;
+    Ok([
+        read_key(&dir.join(KEY_FILE))?,
+        read_key(&dir.join(NEXT_KEY_FILE))?,
+    ]
+    .into_iter()
+    .flatten()
+    .collect())
+}
+
+fn cipher(key: &[u8; KEY_LEN]) -> Result<LessSafeKey, AppError> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| AppError::internal("Invalid vault key"))
+}
+
+// 以密钥名作为附加数据，密文不能被挪用到其它名称下
+fn encrypt(key: &[u8; KEY_LEN], name: &str, plaintext: &str) -> Result<String, AppError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| AppError::internal("Failed to generate nonce"))?;
+
+    let mut in_out = plaintext.as_bytes().to_vec();
+    cipher(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(name.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| AppError::internal(format!("Failed to encrypt secret '{}'", name)))?;
+
+    let mut output = nonce.to_vec();
+    output.extend(in_out);
+    Ok(BASE64.encode(output))
+}
+
+fn decrypt(keys: &[[u8; KEY_LEN]], name: &str, value: &str) -> Result<String, AppError> {
+    let data = BASE64.decode(value).map_err(|_| secret_corrupted(name))?;
+    if data.len() < NONCE_LEN {
+        return Err(secret_corrupted(name));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+
+    for key in keys {
+        let mut in_out = ciphertext.to_vec();
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| secret_corrupted(name))?;
+        if let Ok(plaintext) =
+            cipher(key)?.open_in_place(nonce, Aad::from(name.as_bytes()), &mut in_out)
+        {
+            return String::from_utf8(plaintext.to_vec()).map_err(|_| secret_corrupted(name));
+        }
+    }
+
+    Err(AppError::localized(
+        ErrorCode::VaultKeyInvalid,
+        Message::VaultKeyMismatch,
+        &[("secret", name)],
+    )
+    .with("secret", name))
+}
+
+fn path_value(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn secret_corrupted(name: &str) -> AppError {
+    AppError::localized(
+        ErrorCode::SecretCorrupted,
+        Message::SecretCorrupted,
+        &[("secret", name)],
+    )
+    .with("secret", name)
+}
+
+fn secret_not_found(name: &str) -> AppError {
+    AppError::localized(
+        ErrorCode::SecretNotFound,
+        Message::SecretNotFound,
+        &[("secret", name)],
+    )
+    .with("secret", name)
+}
+
+fn reference(name: &str) -> String {
+    format!("{}{}}}", REFERENCE_PREFIX, name)
+}
+
+// 字符串中引用的密钥名称
+fn referenced_names(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(REFERENCE_PREFIX) {
+        let after = &rest[start + REFERENCE_PREFIX.len()..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        names.push(&after[..end]);
+        rest = &after[end + 1..];
+    }
+    names
+}
+
+pub fn walk_strings<'a>(value: &'a JsonValue, pointer: String, f: &mut impl FnMut(&str, &'a str)) {
+    match value {
+        JsonValue::String(text) => f(&pointer, text),
+        JsonValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                walk_strings(item, format!("{}/{}", pointer, index), f);
+            }
+        }
+        JsonValue::Object(map) => {
+            for (key, item) in map {
+                let child = format!(
+                    "{}/{}",
+                    pointer,
+                    crate::config_diff::escape_pointer_token(key)
+                );
+                walk_strings(item, child, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+// 已解密的密钥，按需读取
+struct Vault {
+    secrets: IndexMap<String, StoredSecret>,
+    keys: Option<Vec<[u8; KEY_LEN]>>,
+}
+
+impl Vault {
+    fn open<R: Runtime>(app: &AppHandle<R>) -> Result<Self, AppError> {
+        Ok(Vault {
+            secrets: store::load_secrets(app)?,
+            keys: None,
+        })
+    }
+
+    fn reveal<R: Runtime>(&mut self, app: &AppHandle<R>, name: &str) -> Result<String, AppError> {
+        let secret = self
+            .secrets
+            .get(name)
+            .ok_or_else(|| secret_not_found(name))?;
+        if self.keys.is_none() {
+            self.keys = Some(load_keys(app)?);
+        }
+        decrypt(
+            self.keys.as_deref().unwrap_or_default(),
+            name,
+            &secret.value,
+        )
+    }
+
+    fn resolve_text<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        text: &str,
+    ) -> Result<String, AppError> {
+        let mut output = text.to_string();
+        for name in referenced_names(text) {
+            let value = self.reveal(app, name)?;
+            output = output.replace(&reference(name), &value);
+        }
+        Ok(output)
+    }
+
+    fn resolve_value<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        value: &mut JsonValue,
+    ) -> Result<(), AppError> {
+        match value {
+            JsonValue::String(text) if text.contains(REFERENCE_PREFIX) => {
+                *text = self.resolve_text(app, text)?;
+            }
+            JsonValue::Array(items) => {
+                for item in items {
+                    self.resolve_value(app, item)?;
+                }
+            }
+            JsonValue::Object(map) => {
+                for (_, item) in map.iter_mut() {
+                    self.resolve_value(app, item)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+// 将 JSON 中的密钥引用替换为明文
+pub fn resolve_value<R: Runtime>(
+    app: &AppHandle<R>,
+    value: &mut JsonValue,
+) -> Result<(), AppError> {
+    Vault::open(app)?.resolve_value(app, value)
+}
+
+fn find_usages<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<Vec<SecretUsage>, AppError> {
+    let mut usages = Vec::new();
+
+    let mut installed: Vec<_> = store::load_installed_servers(app)?.into_iter().collect();
+    installed.sort_by(|a, b| a.0.cmp(&b.0));
+    for (server, installed_server) in installed {
+        let value = serde_json::to_value(&installed_server.entry)
+            .map_err(|e| AppError::internal(format!("Failed to serialize server: {}", e)))?;
+        walk_strings(&value, String::new(), &mut |pointer, text| {
+            if referenced_names(text).contains(&name) {
+                usages.push(SecretUsage {
+                    server: server.clone(),
+                    profile: None,
+                    client: None,
+                    pointer: pointer.to_string(),
+                });
+            }
+        });
+    }
+
+    for (profile, server_profile) in store::load_profiles(app)? {
+        for (server, server_override) in &server_profile.overrides {
+            let value = serde_json::to_value(server_override)
+                .map_err(|e| AppError::internal(format!("Failed to serialize profile: {}", e)))?;
+            walk_strings(&value, String::new(), &mut |pointer, text| {
+                if referenced_names(text).contains(&name) {
+                    usages.push(SecretUsage {
+                        server: server.clone(),
+                        profile: Some(profile.clone()),
+                        client: None,
+                        pointer: pointer.to_string(),
+                    });
+                }
+            });
+        }
+    }
+
+    // 禁用的 server 保存的配置中保留着引用，重新启用时仍然需要这些密钥
+    for disabled in store::load_disabled_servers(app)? {
+        walk_strings(&disabled.config, String::new(), &mut |pointer, text| {
+            if referenced_names(text).contains(&name) {
+                usages.push(SecretUsage {
+                    server: disabled.name.clone(),
+                    profile: None,
+                    client: Some(disabled.client),
+                    pointer: pointer.to_string(),
+                });
+            }
+        });
+    }
+
+    Ok(usages)
+}
+
+// 密钥变化后，重新生成引用它的 server 在各客户端中的配置；
+// 禁用的 server 不在客户端配置中，启用时才会解析
+fn refresh_servers<R: Runtime>(app: &AppHandle<R>, usages: &[SecretUsage]) -> Result<(), AppError> {
+    let installed = store::load_installed_servers(app)?;
+    let active_profile = store::load_active_profile(app)?;
+    let mut servers: Vec<&str> = usages
+        .iter()
+        .filter(|usage| usage.client.is_none())
+        .filter(|usage| usage.profile.is_none() || usage.profile == active_profile)
+        .map(|usage| usage.server.as_str())
+        .collect();
+    servers.sort_unstable();
+    servers.dedup();
+
+    for name in servers {
+        let Some(server) = installed.get(name) else {
+            continue;
+        };
+        // 保留当前 profile 对该 server 的覆盖
+        let entry = crate::profiles::active_entry(app, name, &server.entry)?;
+        let entry = crate::variables::resolve_entry(app, &entry)?;
+        for client in get_server_clients(name.to_string()) {
+            modify_client_config::<_, AppError>(client, |config| config.set_server(name, &entry))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<(), AppError> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+    {
+        return Err(AppError::localized(
+            ErrorCode::SecretNameInvalid,
+            Message::SecretNameInvalid,
+            &[("secret", name)],
+        )
+        .with("secret", name));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_secrets(app: AppHandle) -> Result<Vec<SecretInfo>, AppError> {
+    store::load_secrets(&app)?
+        .into_iter()
+        .map(|(name, secret)| {
+            Ok(SecretInfo {
+                used_by: find_usages(&app, &name)?,
+                name,
+                created_at: secret.created_at,
+                updated_at: secret.updated_at,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn set_secret(app: AppHandle, name: String, value: String) -> Result<(), AppError> {
+    validate_name(&name)?;
+
+    let mut secrets = store::load_secrets(&app)?;
+    if secrets.contains_key(&name) {
+        return Err(AppError::localized(
+            ErrorCode::SecretAlreadyExists,
+            Message::SecretAlreadyExists,
+            &[("secret", &name)],
+        )
+        .with("secret", name.as_str()));
+    }
+
+    let key = load_or_create_key(&app)?;
+    let now = chrono::Local::now().to_rfc3339();
+    secrets.insert(
+        name.clone(),
+        StoredSecret {
+            value: encrypt(&key, &name, &value)?,
+            created_at: now.clone(),
+            updated_at: now,
+        },
+    );
+    store::save_secrets(&app, &secrets)
+}
+
+// 更换密钥的值，并更新所有引用它的客户端配置
+#[tauri::command]
+pub fn rotate_secret(app: AppHandle, name: String, value: String) -> Result<(), AppError> {
+    let mut secrets = store::load_secrets(&app)?;
+    let secret = secrets
+        .get_mut(&name)
+        .ok_or_else(|| secret_not_found(&name))?;
+
+    let key = load_or_create_key(&app)?;
+    secret.value = encrypt(&key, &name, &value)?;
+    secret.updated_at = chrono::Local::now().to_rfc3339();
+    store::save_secrets(&app, &secrets)?;
+
+    refresh_servers(&app, &find_usages(&app, &name)?)
+}
+
+#[tauri::command]
+pub fn delete_secret(app: AppHandle, name: String) -> Result<(), AppError> {
+    let usages = find_usages(&app, &name)?;
+    if !usages.is_empty() {
+        let mut servers: Vec<&str> = usages.iter().map(|usage| usage.server.as_str()).collect();
+        servers.sort_unstable();
+        servers.dedup();
+        return Err(AppError::localized(
+            ErrorCode::SecretInUse,
+            Message::SecretInUse,
+            &[("secret", &name), ("servers", &servers.join(", "))],
+        )
+        .with("secret", name.as_str())
+        .with("servers", serde_json::json!(servers)));
+    }
+
+    let mut secrets = store::load_secrets(&app)?;
+    if secrets.shift_remove(&name).is_none() {
+        return Err(secret_not_found(&name));
+    }
+    store::save_secrets(&app, &secrets)
+}
+
+#[tauri::command]
+pub fn get_secret_usages(app: AppHandle, name: String) -> Result<Vec<SecretUsage>, AppError> {
+    find_usages(&app, &name)
+}
+
+// 把已安装 server 中的 env 或 header 明文移入密钥库，改为引用
+#[tauri::command]
+pub fn move_value_to_secret(
+    app: AppHandle,
+    server: String,
+    key: String,
+    name: String,
+) -> Result<(), AppError> {
+    store::update_installed_servers(&app, |servers| {
+        let installed_server = servers
+            .get_mut(&server)
+            .ok_or_else(|| AppError::server_not_found(&server))?;
+
+        let values = match &mut installed_server.entry {
+            McpServerEntry::Stdio(stdio) => stdio.env.as_mut(),
+            McpServerEntry::Remote(remote) => remote.headers.as_mut(),
+        };
+        let value = values
+            .and_then(|values| values.get_mut(&key))
+            .ok_or_else(|| {
+                AppError::localized(
+                    ErrorCode::InvalidArguments,
+                    Message::SecretValueNotSet,
+                    &[("key", &key), ("server", &server)],
+                )
+                .with("server", server.as_str())
+                .with("key", key.as_str())
+            })?;
+        if value.contains(REFERENCE_PREFIX) {
+            return Err(AppError::localized(
+                ErrorCode::InvalidArguments,
+                Message::SecretValueIsReference,
+                &[("key", &key)],
+            )
+            .with("server", server.as_str())
+            .with("key", key.as_str()));
+        }
+
+        set_secret(app.clone(), name.clone(), value.clone())?;
+        *value = reference(&name);
+        Ok(())
+    })
+}
+
+// 更换主密钥并重新加密全部密钥
+#[tauri::command]
+pub fn rotate_vault_key(app: AppHandle) -> Result<(), AppError> {
+    let mut secrets = store::load_secrets(&app)?;
+    let keys = load_keys(&app)?;
+    let new_key = generate_key()?;
+
+    for (name, secret) in secrets.iter_mut() {
+        let plaintext = decrypt(&keys, name, &secret.value)?;
+        secret.value = encrypt(&new_key, name, &plaintext)?;
+    }
+
+    // 新密钥先落盘，store 写入失败时仍能用旧密钥解密
+    let dir = key_dir(&app)?;
+    let next_path = dir.join(NEXT_KEY_FILE);
+    write_key(&next_path, &new_key)?;
+    store::save_secrets(&app, &secrets)?;
+
+    fs::rename(&next_path, dir.join(KEY_FILE))
+        .map_err(|e| AppError::io(Message::VaultKeyWriteFailed, &next_path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(byte: u8) -> [u8; KEY_LEN] {
+        [byte; KEY_LEN]
+    }
+
+    #[test]
+    fn decrypts_what_it_encrypts() {
+        let key = test_key(1);
+        let value = encrypt(&key, "github", "ghp_secret").unwrap();
+        assert_ne!(value, "ghp_secret");
+        assert_eq!(decrypt(&[key], "github", &value).unwrap(), "ghp_secret");
+        // 每次加密使用新的 nonce
+        assert_ne!(encrypt(&key, "github", "ghp_secret").unwrap(), value);
+    }
+
+    #[test]
+    fn ciphertext_is_bound_to_the_secret_name() {
+        let key = test_key(1);
+        let value = encrypt(&key, "github", "ghp_secret").unwrap();
+        let error = decrypt(&[key], "gitlab", &value).unwrap_err();
+        assert_eq!(error.code, ErrorCode::VaultKeyInvalid);
+    }
+
+    #[test]
+    fn tries_every_key_during_rotation() {
+        let old_key = test_key(1);
+        let new_key = test_key(2);
+        let value = encrypt(&old_key, "api", "token").unwrap();
+        assert_eq!(
+            decrypt(&[new_key, old_key], "api", &value).unwrap(),
+            "token"
+        );
+
+        let error = decrypt(&[new_key], "api", &value).unwrap_err();
+        assert_eq!(error.code, ErrorCode::VaultKeyInvalid);
+    }
+
+    #[test]
+    fn reports_corrupted_values() {
+        let key = test_key(1);
+        for value in ["not base64!", "AAAA"] {
+            let error = decrypt(&[key], "api", value).unwrap_err();
+            assert_eq!(error.code, ErrorCode::SecretCorrupted);
+        }
+    }
+
+    #[test]
+    fn finds_referenced_names() {
+        assert_eq!(
+            referenced_names("Bearer ${secret:a} ${env:B} ${secret:c.d}"),
+            vec!["a", "c.d"]
+        );
+        assert!(referenced_names("${secret:unterminated").is_empty());
+    }
+}
@@ -0,0 +1,484 @@
+use crate::claude_config::write_atomic;
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::mcp_clients::{read_client_config, target_clients, McpClient};
+use crate::mcp_servers::install_servers;
+use crate::server_entry::McpServerEntry;
+use crate::store::{self, InstalledMcpServer};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+const BUNDLE_VERSION: u32 = 1;
+
+// 名称中包含这些词的 env/header 视为密钥
+const SECRET_KEY_PATTERNS: &[&str] = &[
+    "KEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "AUTH",
+    "PRIVATE",
+];
+
+// 常见密钥的值前缀
+const SECRET_VALUE_PREFIXES: &[&str] = &[
+    "sk-",
+    "ghp_",
+    "gho_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "Bearer ",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleFormat {
+    Json,
+    Toml,
+}
+
+// 安装时的元数据，手动添加的 server 没有
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledMetadata {
+    pub id: String,
+    pub require_file_path: bool,
+    pub repo_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledServer {
+    pub config: McpServerEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BundledMetadata>,
+}
+
+// 导出时替换掉的密钥，导入时需要用户填写
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundlePlaceholder {
+    pub name: String,
+    pub server: String,
+    // "env" 或 "headers"
+    pub field: String,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub servers: IndexMap<String, BundledServer>,
+    #[serde(default)]
+    pub placeholders: Vec<BundlePlaceholder>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionStrategy {
+    // 有重名时拒绝导入
+    #[default]
+    Fail,
+    Skip,
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleServerPreview {
+    pub name: String,
+    pub transport: &'static str,
+    // 已安装到 store 或已存在于目标客户端配置中
+    pub collision: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+// 单个 server 的导入结果，某个 server 失败不影响其它 server
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleImportResult {
+    pub name: String,
+    pub status: ImportStatus,
+    pub error: Option<AppError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundlePreview {
+    pub servers: Vec<BundleServerPreview>,
+    pub placeholders: Vec<BundlePlaceholder>,
+}
+
+fn is_secret(key: &str, value: &str) -> bool {
+    if value.is_empty() || value.starts_with("${") {
+        return false;
+    }
+
+    let key = key.to_uppercase();
+    SECRET_KEY_PATTERNS
+        .iter()
+        .any(|pattern| key.contains(pattern))
+        || SECRET_VALUE_PREFIXES
+            .iter()
+            .any(|prefix| value.starts_with(prefix))
+}
+
+fn placeholder_name(server: &str, key: &str) -> String {
+    format!("{}_{}", server, key)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn placeholder_value(name: &str) -> String {
+    format!("${{placeholder:{}}}", name)
+}
+
+// 将密钥替换为占位符
+fn redact_map(
+    server: &str,
+    field: &str,
+    values: Option<&mut IndexMap<String, String>>,
+    placeholders: &mut Vec<BundlePlaceholder>,
+) {
+    let Some(values) = values else {
+        return;
+    };
+
+    for (key, value) in values.iter_mut() {
+        if !is_secret(key, value) {
+            continue;
+        }
+
+        let mut name = placeholder_name(server, key);
+        if placeholders.iter().any(|p| p.name == name) {
+            name = format!("{}_{}", name, placeholders.len());
+        }
+        *value = placeholder_value(&name);
+        placeholders.push(BundlePlaceholder {
+            name,
+            server: server.to_string(),
+            field: field.to_string(),
+            key: key.clone(),
+        });
+    }
+}
+
+fn fill_map(
+    server: &str,
+    field: &str,
+    values: Option<&mut IndexMap<String, String>>,
+    placeholders: &[BundlePlaceholder],
+    answers: &HashMap<String, String>,
+) -> Result<(), AppError> {
+    let Some(values) = values else {
+        return Ok(());
+    };
+
+    for placeholder in placeholders
+        .iter()
+        .filter(|p| p.server == server && p.field == field)
+    {
+        if let Some(value) = values.get_mut(&placeholder.key) {
+            *value = answers.get(&placeholder.name).cloned().ok_or_else(|| {
+                AppError::localized(
+                    ErrorCode::InvalidArguments,
+                    Message::PlaceholderMissing,
+                    &[("placeholder", &placeholder.name)],
+                )
+                .with("placeholder", placeholder.name.as_str())
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn entry_maps(entry: &mut McpServerEntry) -> (Option<&mut IndexMap<String, String>>, &str) {
+    match entry {
+        McpServerEntry::Stdio(stdio) => (stdio.env.as_mut(), "env"),
+        McpServerEntry::Remote(remote) => (remote.headers.as_mut(), "headers"),
+    }
+}
+
+fn serialize_bundle(bundle: &ServerBundle, format: BundleFormat) -> Result<String, AppError> {
+    let result = match format {
+        BundleFormat::Json => serde_json::to_string_pretty(bundle).map_err(|e| e.to_string()),
+        BundleFormat::Toml => toml::to_string_pretty(bundle).map_err(|e| e.to_string()),
+    };
+    result.map_err(|e| AppError::internal(format!("Failed to serialize bundle: {}", e)))
+}
+
+fn bundle_parse_error(e: impl std::fmt::Display) -> AppError {
+    AppError::localized(
+        ErrorCode::BundleInvalid,
+        Message::BundleParseFailed,
+        &[("error", &e.to_string())],
+    )
+}
+
+// 未指定格式时根据内容判断
+fn parse_bundle(content: &str, format: Option<BundleFormat>) -> Result<ServerBundle, AppError> {
+    let format = format.unwrap_or(if content.trim_start().starts_with('{') {
+        BundleFormat::Json
+    } else {
+        BundleFormat::Toml
+    });
+
+    let bundle: ServerBundle = match format {
+        BundleFormat::Json => serde_json::from_str(content).map_err(bundle_parse_error)?,
+        BundleFormat::Toml => toml::from_str(content).map_err(bundle_parse_error)?,
+    };
+
+    if bundle.version > BUNDLE_VERSION {
+        return Err(AppError::localized(
+            ErrorCode::BundleInvalid,
+            Message::BundleVersionUnsupported,
+            &[
+                ("version", &bundle.version.to_string()),
+                ("supported", &BUNDLE_VERSION.to_string()),
+            ],
+        )
+        .with("version", bundle.version));
+    }
+
+    Ok(bundle)
+}
+
+fn format_from_path(path: &str) -> Option<BundleFormat> {
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("toml") => Some(BundleFormat::Toml),
+        Some(ext) if ext.eq_ignore_ascii_case("json") => Some(BundleFormat::Json),
+        _ => None,
+    }
+}
+
+fn read_bundle_file(path: &str) -> Result<ServerBundle, AppError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| AppError::io(Message::BundleReadFailed, Path::new(path), e))?;
+    parse_bundle(&content, format_from_path(path))
+}
+
+// 与已安装 server 或目标客户端中已有 server 重名
+fn find_collisions(
+    app: &AppHandle,
+    bundle: &ServerBundle,
+    clients: &[McpClient],
+) -> Result<Vec<String>, AppError> {
+    let installed = store::load_installed_servers(app)?;
+    let configs = clients
+        .iter()
+        .map(|&client| read_client_config(client))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(bundle
+        .servers
+        .keys()
+        .filter(|name| {
+            installed.contains_key(*name) || configs.iter().any(|c| c.contains_server(name))
+        })
+        .cloned()
+        .collect())
+}
+
+#[tauri::command]
+pub fn export_server_bundle(
+    app: AppHandle,
+    names: Vec<String>,
+    path: String,
+    client: Option<McpClient>,
+    format: Option<BundleFormat>,
+) -> Result<ServerBundle, AppError> {
+    let servers = read_client_config(client.unwrap_or(McpClient::ClaudeDesktop))?.servers()?;
+    let installed = store::load_installed_servers(&app)?;
+
+    let mut bundle = ServerBundle {
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        servers: IndexMap::new(),
+        placeholders: Vec::new(),
+    };
+
+    for name in &names {
+        let metadata = installed.get(name);
+        // 优先导出客户端中的配置，以包含用户的修改，变量引用按 store 中的配置还原
+        let mut config = match (servers.get(name), metadata) {
+            (Some(entry), Some(server)) => {
+                let mut entry = entry.clone();
+                crate::variables::restore_entry_references(&app, &server.entry, &mut entry)?;
+                entry
+            }
+            (Some(entry), None) => entry.clone(),
+            (None, Some(server)) => server.entry.clone(),
+            (None, None) => return Err(AppError::server_not_found(name)),
+        };
+
+        let (values, field) = entry_maps(&mut config);
+        redact_map(name, field, values, &mut bundle.placeholders);
+
+        bundle.servers.insert(
+            name.clone(),
+            BundledServer {
+                config,
+                metadata: metadata.map(|server| BundledMetadata {
+                    id: server.id.clone(),
+                    require_file_path: server.require_file_path,
+                    repo_url: server.repo_url.clone(),
+                }),
+            },
+        );
+    }
+
+    let format = format
+        .or_else(|| format_from_path(&path))
+        .unwrap_or(BundleFormat::Json);
+    let content = serialize_bundle(&bundle, format)?;
+    write_atomic(
+        Path::new(&path),
+        content.as_bytes(),
+        Message::BundleWriteFailed,
+    )?;
+
+    Ok(bundle)
+}
+
+// 导入前预览，前端据此提示填写占位符和处理重名
+#[tauri::command]
+pub fn preview_server_bundle(
+    app: AppHandle,
+    path: String,
+    clients: Option<Vec<McpClient>>,
+) -> Result<BundlePreview, AppError> {
+    let bundle = read_bundle_file(&path)?;
+    let collisions = find_collisions(&app, &bundle, &target_clients(clients))?;
+
+    Ok(BundlePreview {
+        servers: bundle
+            .servers
+            .iter()
+            .map(|(name, server)| BundleServerPreview {
+                name: name.clone(),
+                transport: server.config.transport_name(),
+                collision: collisions.contains(name),
+            })
+            .collect(),
+        placeholders: bundle.placeholders,
+    })
+}
+
+#[tauri::command]
+pub fn import_server_bundle(
+    app: AppHandle,
+    path: String,
+    values: HashMap<String, String>,
+    clients: Option<Vec<McpClient>>,
+    on_collision: Option<CollisionStrategy>,
+) -> Result<Vec<BundleImportResult>, AppError> {
+    let bundle = read_bundle_file(&path)?;
+    let clients = target_clients(clients);
+    let collisions = find_collisions(&app, &bundle, &clients)?;
+    let on_collision = on_collision.unwrap_or_default();
+
+    if on_collision == CollisionStrategy::Fail && !collisions.is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::ServerAlreadyInstalled,
+            Message::ServersAlreadyInstalled,
+            &[("servers", &collisions.join(", "))],
+        )
+        .with("servers", serde_json::json!(collisions)));
+    }
+
+    let skipped = |name: &str| {
+        on_collision == CollisionStrategy::Skip && collisions.iter().any(|c| c == name)
+    };
+
+    // 只检查实际导入的 server 用到的占位符
+    let missing: Vec<&str> = bundle
+        .placeholders
+        .iter()
+        .filter(|p| !skipped(&p.server) && !values.contains_key(&p.name))
+        .map(|p| p.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::InvalidArguments,
+            Message::PlaceholdersMissing,
+            &[("placeholders", &missing.join(", "))],
+        )
+        .with("placeholders", serde_json::json!(missing)));
+    }
+
+    // 先填好所有占位符，再一起安装：在所有目标客户端上试运行，任何一个 server
+    // 无效时不写入任何客户端
+    let mut results = Vec::new();
+    let mut servers = Vec::new();
+    for (name, server) in bundle.servers {
+        if skipped(&name) {
+            results.push(BundleImportResult {
+                name,
+                status: ImportStatus::Skipped,
+                error: None,
+            });
+            continue;
+        }
+
+        let mut entry = server.config;
+        let (maps, field) = entry_maps(&mut entry);
+        fill_map(&name, field, maps, &bundle.placeholders, &values)?;
+
+        let metadata = server.metadata.unwrap_or(BundledMetadata {
+            id: name.clone(),
+            require_file_path: false,
+            repo_url: String::new(),
+        });
+        servers.push(InstalledMcpServer {
+            id: metadata.id,
+            name,
+            entry,
+            require_file_path: metadata.require_file_path,
+            repo_url: metadata.repo_url,
+            restart_policy: Default::default(),
+            crashes: Vec::new(),
+        });
+    }
+    if servers.is_empty() {
+        return Ok(results);
+    }
+    install_servers(&app, servers.clone(), &clients, true)?;
+
+    // 每个客户端的配置只写一次，所有 server 一起写入或都不写入；但写入时仍可能因为
+    // 配置被外部修改等原因失败，这时已写入的客户端保留导入的 server，
+    // 每个 server 的结果中都带上这个错误
+    let names: Vec<String> = servers.iter().map(|server| server.name.clone()).collect();
+    let error = install_servers(&app, servers, &clients, false).err();
+    for name in names {
+        results.push(BundleImportResult {
+            name,
+            status: if error.is_none() {
+                ImportStatus::Imported
+            } else {
+                ImportStatus::Failed
+            },
+            error: error.clone(),
+        });
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,183 @@
+use indexmap::IndexMap;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+// 通过本地进程 (stdin/stdout) 通信的 server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StdioServerConfig {
+    pub command: String,
+    // args 缺省时按空数组处理
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<IndexMap<String, String>>,
+    // 进程的工作目录，未指定时使用 MCP Manager 的当前目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    // 未识别的字段原样保留
+    #[serde(flatten)]
+    pub extra: Map<String, JsonValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemoteTransport {
+    #[serde(rename = "sse")]
+    Sse,
+    #[serde(rename = "http")]
+    StreamableHttp,
+}
+
+// 通过 URL 连接的远程 server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteServerConfig {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<RemoteTransport>,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<IndexMap<String, String>>,
+    #[serde(flatten)]
+    pub extra: Map<String, JsonValue>,
+}
+
+impl RemoteServerConfig {
+    // 未指定 type 时，根据 URL 推断传输方式
+    pub fn effective_transport(&self) -> RemoteTransport {
+        match self.transport {
+            Some(transport) => transport,
+            None if self.url.trim_end_matches('/').ends_with("/sse") => RemoteTransport::Sse,
+            None => RemoteTransport::StreamableHttp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum McpServerEntry {
+    Stdio(StdioServerConfig),
+    Remote(RemoteServerConfig),
+}
+
+impl<'de> Deserialize<'de> for McpServerEntry {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Map::<String, JsonValue>::deserialize(deserializer)?;
+
+        // 有 url 且没有 command 的视为远程 server，其余都按本地进程解析，
+        // 这样缺字段时能得到具体的错误信息
+        if value.contains_key("url") && !value.contains_key("command") {
+            serde_json::from_value(JsonValue::Object(value))
+                .map(McpServerEntry::Remote)
+                .map_err(D::Error::custom)
+        } else {
+            serde_json::from_value(JsonValue::Object(value))
+                .map(McpServerEntry::Stdio)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+impl McpServerEntry {
+    pub fn stdio(
+        command: String,
+        args: Vec<String>,
+        env: Option<IndexMap<String, String>>,
+    ) -> Self {
+        McpServerEntry::Stdio(StdioServerConfig {
+            command,
+            args,
+            env,
+            cwd: None,
+            extra: Map::new(),
+        })
+    }
+
+    pub fn remote(
+        url: String,
+        transport: Option<RemoteTransport>,
+        headers: Option<IndexMap<String, String>>,
+    ) -> Self {
+        McpServerEntry::Remote(RemoteServerConfig {
+            transport,
+            url,
+            headers,
+            extra: Map::new(),
+        })
+    }
+
+    pub fn transport_name(&self) -> &'static str {
+        match self {
+            McpServerEntry::Stdio(_) => "stdio",
+            McpServerEntry::Remote(remote) => match remote.effective_transport() {
+                RemoteTransport::Sse => "sse",
+                RemoteTransport::StreamableHttp => "http",
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InstalledMcpServer;
+    use serde_json::json;
+
+    #[test]
+    fn parses_stdio_and_remote_entries() {
+        let stdio: McpServerEntry =
+            serde_json::from_value(json!({ "command": "npx", "args": ["-y", "server"] })).unwrap();
+        assert!(matches!(stdio, McpServerEntry::Stdio(ref s) if s.args.len() == 2));
+
+        let remote: McpServerEntry =
+            serde_json::from_value(json!({ "url": "https://example.com/sse" })).unwrap();
+        assert_eq!(remote.transport_name(), "sse");
+
+        // 同时有 command 和 url 时按本地进程处理
+        let both: McpServerEntry =
+            serde_json::from_value(json!({ "command": "node", "url": "https://example.com" }))
+                .unwrap();
+        assert!(matches!(both, McpServerEntry::Stdio(_)));
+    }
+
+    #[test]
+    fn keeps_unknown_fields() {
+        let value = json!({
+            "command": "node",
+            "args": [],
+            "disabled": true,
+            "autoApprove": ["a"]
+        });
+        let entry: McpServerEntry = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&entry).unwrap(), value);
+    }
+
+    #[test]
+    fn reports_missing_fields() {
+        let error = serde_json::from_value::<McpServerEntry>(json!({ "args": [] })).unwrap_err();
+        assert!(error.to_string().contains("command"));
+    }
+
+    #[test]
+    fn reads_legacy_store_records() {
+        let server: InstalledMcpServer = serde_json::from_value(json!({
+            "id": "1",
+            "name": "filesystem",
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+            "env": null,
+            "require_file_path": true,
+            "repo_url": "https://github.com/modelcontextprotocol/servers"
+        }))
+        .unwrap();
+
+        let McpServerEntry::Stdio(stdio) = &server.entry else {
+            panic!("expected a stdio entry");
+        };
+        assert_eq!(stdio.command, "npx");
+        assert!(stdio.env.is_none());
+        assert!(stdio.extra.is_empty());
+        assert!(server.crashes.is_empty());
+    }
+}
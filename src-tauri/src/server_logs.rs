@@ -0,0 +1,214 @@
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use tauri::ipc::Channel;
+
+// 每个 server 最多保留的行数，超出后丢弃最早的行
+const MAX_LINES_PER_SERVER: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    // 全局递增的序号，用于增量获取
+    pub seq: u64,
+    pub server: String,
+    pub stream: LogStream,
+    pub timestamp: String,
+    pub line: String,
+}
+
+// 实时订阅，server 为 None 时接收所有 server 的输出
+struct Subscriber {
+    server: Option<String>,
+    channel: Channel<LogLine>,
+}
+
+static SERVER_LOGS: Lazy<Mutex<HashMap<String, VecDeque<LogLine>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static SUBSCRIBERS: Lazy<Mutex<HashMap<u64, Subscriber>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(1);
+
+fn record_line(server: &str, stream: LogStream, line: String) {
+    let entry = LogLine {
+        seq: NEXT_SEQ.fetch_add(1, Ordering::Relaxed),
+        server: server.to_string(),
+        stream,
+        timestamp: chrono::Local::now().to_rfc3339(),
+        line,
+    };
+
+    crate::log_files::append_line(&entry);
+
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        // 前端关闭后发送会失败，顺便移除该订阅
+        subscribers.retain(|_, subscriber| {
+            if subscriber
+                .server
+                .as_deref()
+                .is_some_and(|name| name != server)
+            {
+                return true;
+            }
+            subscriber.channel.send(entry.clone()).is_ok()
+        });
+    }
+
+    if let Ok(mut logs) = SERVER_LOGS.lock() {
+        let lines = logs.entry(server.to_string()).or_default();
+        if lines.len() >= MAX_LINES_PER_SERVER {
+            lines.pop_front();
+        }
+        lines.push_back(entry);
+    }
+}
+
+// 在新线程中逐行读取进程输出，非 UTF-8 的内容按有损方式转换；
+// 每行记录后交给 on_line 处理，输出结束时以 None 调用一次
+pub fn capture_output<R, F>(server: &str, stream: LogStream, output: R, mut on_line: F)
+where
+    R: Read + Send + 'static,
+    F: FnMut(Option<&str>) + Send + 'static,
+{
+    let server = server.to_string();
+    thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer) {
+                Ok(0) => break,
+                Ok(_) => {
+                    let line = String::from_utf8_lossy(&buffer)
+                        .trim_end_matches(['\r', '\n'])
+                        .to_string();
+                    record_line(&server, stream, line.clone());
+                    on_line(Some(&line));
+                }
+                Err(e) => {
+                    eprintln!("Failed to read output of {}: {}", server, e);
+                    break;
+                }
+            }
+        }
+        on_line(None);
+    });
+}
+
+// 某个输出流最近的 count 行
+pub fn tail_lines(server: &str, stream: LogStream, count: usize) -> Vec<String> {
+    let Ok(logs) = SERVER_LOGS.lock() else {
+        return Vec::new();
+    };
+    let Some(lines) = logs.get(server) else {
+        return Vec::new();
+    };
+    let mut tail: Vec<String> = lines
+        .iter()
+        .rev()
+        .filter(|line| line.stream == stream)
+        .take(count)
+        .map(|line| line.line.clone())
+        .collect();
+    tail.reverse();
+    tail
+}
+
+#[tauri::command]
+pub fn get_server_logs(
+    name: String,
+    stream: Option<LogStream>,
+    // 只返回序号大于 after 的行
+    after: Option<u64>,
+    // 只返回包含该文本的行，不区分大小写
+    search: Option<String>,
+    // 最多返回最近的 limit 行
+    limit: Option<usize>,
+) -> Result<Vec<LogLine>, AppError> {
+    let logs = SERVER_LOGS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock server logs: {}", e)))?;
+    let Some(lines) = logs.get(&name) else {
+        return Ok(Vec::new());
+    };
+
+    let search = search.map(|search| search.to_lowercase());
+    let mut matched: Vec<LogLine> = lines
+        .iter()
+        .filter(|line| stream.is_none_or(|stream| line.stream == stream))
+        .filter(|line| after.is_none_or(|after| line.seq > after))
+        .filter(|line| {
+            search
+                .as_deref()
+                .is_none_or(|search| line.line.to_lowercase().contains(search))
+        })
+        .cloned()
+        .collect();
+
+    if let Some(limit) = limit {
+        let skip = matched.len().saturating_sub(limit);
+        matched.drain(..skip);
+    }
+    Ok(matched)
+}
+
+#[tauri::command]
+pub fn clear_server_logs(name: String) -> Result<(), AppError> {
+    SERVER_LOGS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock server logs: {}", e)))?
+        .remove(&name);
+    Ok(())
+}
+
+// 订阅新产生的输出，返回用于取消订阅的 id
+#[tauri::command]
+pub fn subscribe_server_logs(
+    name: Option<String>,
+    on_line: Channel<LogLine>,
+) -> Result<u64, AppError> {
+    let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed);
+    SUBSCRIBERS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock log subscribers: {}", e)))?
+        .insert(
+            id,
+            Subscriber {
+                server: name,
+                channel: on_line,
+            },
+        );
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn unsubscribe_server_logs(id: u64) -> Result<(), AppError> {
+    SUBSCRIBERS
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock log subscribers: {}", e)))?
+        .remove(&id);
+    Ok(())
+}
@@ -1,86 +1,313 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::{Language, Message};
+use crate::journal::Journal;
+use crate::mcp_clients::McpClient;
+use crate::mcp_runner::EnvInheritPolicy;
+use crate::profiles::ServerProfile;
+use crate::secrets::StoredSecret;
+use crate::server_entry::McpServerEntry;
+use crate::supervisor::{CrashRecord, RestartPolicy};
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json;
+use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tauri::{AppHandle, Wry};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Runtime};
 use tauri_plugin_store::{Store, StoreBuilder};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct InstalledMcpServer {
     pub id: String,
     pub name: String,
-    pub command: String,
-    pub args: Vec<String>,
-    pub env: Option<HashMap<String, String>>,
+    // 兼容旧数据：command/args/env 与 url/type/headers 都平铺在顶层
+    #[serde(flatten)]
+    pub entry: McpServerEntry,
     pub require_file_path: bool,
     pub repo_url: String,
+    // 进程退出后的重启策略
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    // 最近的崩溃记录，最旧的在前
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crashes: Vec<CrashRecord>,
+}
+
+// 被禁用的 server，config 为从客户端配置中移除时的原始内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DisabledMcpServer {
+    pub name: String,
+    pub client: McpClient,
+    pub config: JsonValue,
+    // 在 mcpServers 中原来的位置，启用时放回原处
+    pub position: usize,
+    pub disabled_at: String,
 }
 
 const STORE_PATH: &str = ".mcp.servers.dat";
 const SERVERS_KEY: &str = "installed_servers";
+const BACKUP_RETENTION_KEY: &str = "backup_retention";
+const PROFILES_KEY: &str = "profiles";
+const ACTIVE_PROFILE_KEY: &str = "active_profile";
+const DISABLED_SERVERS_KEY: &str = "disabled_servers";
+const SECRETS_KEY: &str = "secrets";
+const VARIABLES_KEY: &str = "variables";
+const WORKSPACE_DIR_KEY: &str = "workspace_dir";
+const JOURNAL_KEY: &str = "journal";
+const LANGUAGE_KEY: &str = "language";
+const CLAUDE_CONFIG_PATH_KEY: &str = "claude_config_path";
+const ENV_INHERIT_POLICY_KEY: &str = "env_inherit_policy";
+const HANDSHAKE_TIMEOUT_KEY: &str = "handshake_timeout_secs";
+
+// 串行化对已安装 server 的修改
+static INSTALLED_SERVERS_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
-fn get_store(app: &AppHandle) -> Result<Arc<Store<Wry>>, String> {
+fn store_error(message: Message, e: impl std::fmt::Display) -> AppError {
+    AppError::localized(ErrorCode::Store, message, &[("error", &e.to_string())])
+        .with("store", STORE_PATH)
+}
+
+fn get_store<R: Runtime>(app: &AppHandle<R>) -> Result<Arc<Store<R>>, AppError> {
     let path = PathBuf::from(STORE_PATH);
     StoreBuilder::new(app, path)
         .build()
-        .map_err(|e| format!("Failed to create store: {}", e))
+        .map_err(|e| store_error(Message::StoreOpenFailed, e))
 }
 
-fn save_store(store: &Store<Wry>) -> Result<(), String> {
+fn save_store<R: Runtime>(store: &Store<R>) -> Result<(), AppError> {
     store
         .save()
-        .map_err(|e| format!("Failed to save store: {}", e))
+        .map_err(|e| store_error(Message::StoreSaveFailed, e))
 }
 
-#[tauri::command]
-pub async fn save_installed_server(
-    app: AppHandle,
-    server: InstalledMcpServer,
-) -> Result<(), String> {
-    let store = get_store(&app)?;
+fn load_key<R: Runtime, T: DeserializeOwned>(
+    app: &AppHandle<R>,
+    key: &str,
+) -> Result<Option<T>, AppError> {
+    let store = get_store(app)?;
 
-    let mut servers: HashMap<String, InstalledMcpServer> = store
-        .get(SERVERS_KEY)
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+    // 无法解析时返回错误，不能当作没有数据，否则下次保存会覆盖掉原有的内容
+    match store.get(key) {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| {
+                AppError::localized(
+                    ErrorCode::Store,
+                    Message::StoreReadFailed,
+                    &[("key", key), ("error", &e.to_string())],
+                )
+                .with("store", STORE_PATH)
+                .with("key", key)
+            }),
+    }
+}
 
-    servers.insert(server.name.clone(), server);
+fn save_key<R: Runtime, T: Serialize>(
+    app: &AppHandle<R>,
+    key: &str,
+    value: &T,
+) -> Result<(), AppError> {
+    let store = get_store(app)?;
 
-    store.set(SERVERS_KEY.to_string(), serde_json::json!(servers));
+    store.set(key.to_string(), serde_json::json!(value));
     save_store(&store)?;
 
     Ok(())
 }
 
+pub fn load_installed_servers<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<HashMap<String, InstalledMcpServer>, AppError> {
+    Ok(load_key(app, SERVERS_KEY)?.unwrap_or_default())
+}
+
+fn save_installed_servers<R: Runtime>(
+    app: &AppHandle<R>,
+    servers: &HashMap<String, InstalledMcpServer>,
+) -> Result<(), AppError> {
+    save_key(app, SERVERS_KEY, servers)
+}
+
+// 修改已安装的 server 都在锁内读取、修改并保存，避免并发修改时互相覆盖；
+// f 中不能再次调用本函数
+pub fn update_installed_servers<R: Runtime, T>(
+    app: &AppHandle<R>,
+    f: impl FnOnce(&mut HashMap<String, InstalledMcpServer>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let _guard = INSTALLED_SERVERS_LOCK
+        .lock()
+        .map_err(|e| AppError::internal(format!("Failed to lock installed servers: {}", e)))?;
+    let mut servers = load_installed_servers(app)?;
+    let result = f(&mut servers)?;
+    save_installed_servers(app, &servers)?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn save_installed_server(
+    app: AppHandle,
+    server: InstalledMcpServer,
+) -> Result<(), AppError> {
+    update_installed_servers(&app, |servers| {
+        servers.insert(server.name.clone(), server);
+        Ok(())
+    })
+}
+
 #[tauri::command]
 pub async fn get_installed_server(
     app: AppHandle,
     name: String,
-) -> Result<Option<InstalledMcpServer>, String> {
-    let store = get_store(&app)?;
-
-    let servers: HashMap<String, InstalledMcpServer> = store
-        .get(SERVERS_KEY)
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+) -> Result<Option<InstalledMcpServer>, AppError> {
+    let servers = load_installed_servers(&app)?;
 
     Ok(servers.get(&name).cloned())
 }
 
 #[tauri::command]
-pub async fn remove_installed_server(app: AppHandle, name: String) -> Result<(), String> {
-    let store = get_store(&app)?;
+pub async fn remove_installed_server(app: AppHandle, name: String) -> Result<(), AppError> {
+    update_installed_servers(&app, |servers| {
+        servers.remove(&name);
+        Ok(())
+    })
+}
+
+pub fn load_backup_retention<R: Runtime>(app: &AppHandle<R>) -> Result<Option<usize>, AppError> {
+    load_key(app, BACKUP_RETENTION_KEY)
+}
 
-    let mut servers: HashMap<String, InstalledMcpServer> = store
-        .get(SERVERS_KEY)
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+pub fn save_backup_retention(app: &AppHandle, retention: usize) -> Result<(), AppError> {
+    save_key(app, BACKUP_RETENTION_KEY, &retention)
+}
 
-    servers.remove(&name);
+pub fn load_profiles<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<IndexMap<String, ServerProfile>, AppError> {
+    Ok(load_key(app, PROFILES_KEY)?.unwrap_or_default())
+}
 
-    store.set(SERVERS_KEY.to_string(), serde_json::json!(servers));
-    save_store(&store)?;
+pub fn save_profiles<R: Runtime>(
+    app: &AppHandle<R>,
+    profiles: &IndexMap<String, ServerProfile>,
+) -> Result<(), AppError> {
+    save_key(app, PROFILES_KEY, profiles)
+}
 
-    Ok(())
+pub fn load_active_profile<R: Runtime>(app: &AppHandle<R>) -> Result<Option<String>, AppError> {
+    load_key(app, ACTIVE_PROFILE_KEY)
+}
+
+pub fn save_active_profile<R: Runtime>(
+    app: &AppHandle<R>,
+    name: Option<&str>,
+) -> Result<(), AppError> {
+    save_key(app, ACTIVE_PROFILE_KEY, &name)
+}
+
+pub fn load_disabled_servers<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<Vec<DisabledMcpServer>, AppError> {
+    Ok(load_key(app, DISABLED_SERVERS_KEY)?.unwrap_or_default())
+}
+
+pub fn save_disabled_servers<R: Runtime>(
+    app: &AppHandle<R>,
+    servers: &[DisabledMcpServer],
+) -> Result<(), AppError> {
+    save_key(app, DISABLED_SERVERS_KEY, &servers)
+}
+
+pub fn load_secrets<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<IndexMap<String, StoredSecret>, AppError> {
+    Ok(load_key(app, SECRETS_KEY)?.unwrap_or_default())
+}
+
+pub fn save_secrets<R: Runtime>(
+    app: &AppHandle<R>,
+    secrets: &IndexMap<String, StoredSecret>,
+) -> Result<(), AppError> {
+    save_key(app, SECRETS_KEY, secrets)
+}
+
+pub fn load_variables<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<IndexMap<String, String>, AppError> {
+    Ok(load_key(app, VARIABLES_KEY)?.unwrap_or_default())
+}
+
+pub fn save_variables<R: Runtime>(
+    app: &AppHandle<R>,
+    variables: &IndexMap<String, String>,
+) -> Result<(), AppError> {
+    save_key(app, VARIABLES_KEY, variables)
+}
+
+pub fn load_workspace_dir<R: Runtime>(app: &AppHandle<R>) -> Result<Option<String>, AppError> {
+    load_key(app, WORKSPACE_DIR_KEY)
+}
+
+pub fn save_workspace_dir<R: Runtime>(
+    app: &AppHandle<R>,
+    path: Option<&str>,
+) -> Result<(), AppError> {
+    save_key(app, WORKSPACE_DIR_KEY, &path)
+}
+
+pub fn load_journal<R: Runtime>(app: &AppHandle<R>) -> Result<Journal, AppError> {
+    Ok(load_key(app, JOURNAL_KEY)?.unwrap_or_default())
+}
+
+pub fn save_journal<R: Runtime>(app: &AppHandle<R>, journal: &Journal) -> Result<(), AppError> {
+    save_key(app, JOURNAL_KEY, journal)
+}
+
+pub fn load_language<R: Runtime>(app: &AppHandle<R>) -> Result<Option<Language>, AppError> {
+    load_key(app, LANGUAGE_KEY)
+}
+
+pub fn save_language<R: Runtime>(
+    app: &AppHandle<R>,
+    language: Option<Language>,
+) -> Result<(), AppError> {
+    save_key(app, LANGUAGE_KEY, &language)
+}
+
+pub fn load_claude_config_path<R: Runtime>(app: &AppHandle<R>) -> Result<Option<String>, AppError> {
+    load_key(app, CLAUDE_CONFIG_PATH_KEY)
+}
+
+pub fn save_claude_config_path<R: Runtime>(
+    app: &AppHandle<R>,
+    path: Option<&str>,
+) -> Result<(), AppError> {
+    save_key(app, CLAUDE_CONFIG_PATH_KEY, &path)
+}
+
+pub fn load_env_inherit_policy<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<EnvInheritPolicy, AppError> {
+    Ok(load_key(app, ENV_INHERIT_POLICY_KEY)?.unwrap_or_default())
+}
+
+pub fn save_env_inherit_policy<R: Runtime>(
+    app: &AppHandle<R>,
+    policy: EnvInheritPolicy,
+) -> Result<(), AppError> {
+    save_key(app, ENV_INHERIT_POLICY_KEY, &policy)
+}
+
+pub fn load_handshake_timeout<R: Runtime>(app: &AppHandle<R>) -> Result<u64, AppError> {
+    Ok(load_key(app, HANDSHAKE_TIMEOUT_KEY)?
+        .unwrap_or(crate::mcp_runner::DEFAULT_HANDSHAKE_TIMEOUT_SECS))
+}
+
+pub fn save_handshake_timeout<R: Runtime>(
+    app: &AppHandle<R>,
+    seconds: u64,
+) -> Result<(), AppError> {
+    save_key(app, HANDSHAKE_TIMEOUT_KEY, &seconds)
 }
@@ -0,0 +1,382 @@
+use crate::error::AppError;
+use crate::mcp_runner::{self, ExitedServer};
+use crate::server_entry::McpServerEntry;
+use crate::server_logs::{self, LogStream};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Runtime};
+
+pub const SERVER_CRASHED_EVENT: &str = "mcp-server-crashed";
+
+// 检查进程是否退出的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+// 每个 server 保留的崩溃记录数量
+const MAX_CRASH_RECORDS: usize = 20;
+// 崩溃记录中保留的 stderr 行数
+const STDERR_TAIL_LINES: usize = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    // 只在退出码不为 0 或被信号终止时重启
+    OnFailure,
+    Always,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    // 第一次重启前的等待时间，之后每次翻倍
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    // window_secs 内最多重启的次数，超过后不再重启
+    pub max_restarts: u32,
+    pub window_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            mode: RestartMode::Never,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+            max_restarts: 5,
+            window_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashRecord {
+    pub at: String,
+    pub exit_code: Option<i32>,
+    // 退出状态或启动失败的原因
+    pub status: String,
+    pub uptime_ms: u64,
+    pub stderr_tail: Vec<String>,
+    // 计划在多久后重启，不重启时为 None
+    pub restart_in_ms: Option<u64>,
+    // 达到重启次数上限而放弃重启
+    pub gave_up: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerCrash {
+    pub server: String,
+    pub record: CrashRecord,
+}
+
+// 每个 server 最近的重启时间，用于计算退避时间和次数上限
+static RESTART_HISTORY: Lazy<Mutex<HashMap<String, VecDeque<Instant>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// 等待重启的 server，手动停止时取消
+static PENDING_RESTARTS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+// 每个 server 被手动停止的次数，以及停止全部 server 的次数；
+// 重启线程在启动前后比较，期间被停止过就放弃这次重启
+static STOP_GENERATIONS: Lazy<Mutex<HashMap<String, u64>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static STOP_ALL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn stop_generation(name: &str) -> (u64, u64) {
+    let server = STOP_GENERATIONS
+        .lock()
+        .ok()
+        .and_then(|generations| generations.get(name).copied())
+        .unwrap_or(0);
+    (STOP_ALL_GENERATION.load(Ordering::SeqCst), server)
+}
+
+pub fn cancel_restart(name: &str) {
+    if let Ok(mut generations) = STOP_GENERATIONS.lock() {
+        *generations.entry(name.to_string()).or_default() += 1;
+    }
+    if let Ok(mut pending) = PENDING_RESTARTS.lock() {
+        pending.remove(name);
+    }
+}
+
+pub fn cancel_all_restarts() {
+    STOP_ALL_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut pending) = PENDING_RESTARTS.lock() {
+        pending.clear();
+    }
+}
+
+// 未通过 MCP Manager 安装的 server 使用默认策略，即不重启
+fn load_policy<R: Runtime>(app: &AppHandle<R>, name: &str) -> RestartPolicy {
+    crate::store::load_installed_servers(app)
+        .ok()
+        .and_then(|servers| {
+            servers
+                .get(name)
+                .map(|server| server.restart_policy.clone())
+        })
+        .unwrap_or_default()
+}
+
+// 返回下次重启前的等待时间，达到上限时返回 None
+fn next_backoff(name: &str, policy: &RestartPolicy) -> Option<Duration> {
+    let mut history = RESTART_HISTORY.lock().ok()?;
+    let restarts = history.entry(name.to_string()).or_default();
+    let window = Duration::from_secs(policy.window_secs);
+    while restarts
+        .front()
+        .is_some_and(|restart| restart.elapsed() > window)
+    {
+        restarts.pop_front();
+    }
+    if restarts.len() >= policy.max_restarts as usize {
+        return None;
+    }
+
+    let backoff = policy
+        .initial_backoff_ms
+        .saturating_mul(1u64 << restarts.len().min(32))
+        .min(policy.max_backoff_ms);
+    restarts.push_back(Instant::now());
+    Some(Duration::from_millis(backoff))
+}
+
+// 只有已安装的 server 才保存崩溃记录
+fn save_crash<R: Runtime>(app: &AppHandle<R>, name: &str, record: &CrashRecord) {
+    let result = crate::store::update_installed_servers(app, |servers| {
+        if let Some(server) = servers.get_mut(name) {
+            server.crashes.push(record.clone());
+            let excess = server.crashes.len().saturating_sub(MAX_CRASH_RECORDS);
+            server.crashes.drain(..excess);
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to save crash record for {}: {}", name, e);
+    }
+}
+
+// 按策略安排重启；异常退出或重启失败时保存崩溃记录并通知前端，正常退出不算崩溃
+fn handle_failure<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    exit_code: Option<i32>,
+    status: String,
+    success: bool,
+    uptime: Duration,
+) {
+    let policy = load_policy(app, name);
+    let should_restart = match policy.mode {
+        RestartMode::Never => false,
+        RestartMode::OnFailure => !success,
+        RestartMode::Always => true,
+    };
+    let backoff = should_restart
+        .then(|| next_backoff(name, &policy))
+        .flatten();
+
+    if success {
+        println!(
+            "MCP server {} exited: {} (restart in {:?})",
+            name, status, backoff
+        );
+    } else {
+        record_crash(
+            app,
+            name,
+            exit_code,
+            status,
+            uptime,
+            should_restart,
+            backoff,
+        );
+    }
+    let _ = crate::tray::update_tray_status(app.clone());
+
+    if let Some(backoff) = backoff {
+        schedule_restart(app.clone(), name.to_string(), backoff);
+    }
+}
+
+fn record_crash<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    exit_code: Option<i32>,
+    status: String,
+    uptime: Duration,
+    should_restart: bool,
+    backoff: Option<Duration>,
+) {
+    let record = CrashRecord {
+        at: chrono::Local::now().to_rfc3339(),
+        exit_code,
+        status,
+        uptime_ms: uptime.as_millis() as u64,
+        stderr_tail: server_logs::tail_lines(name, LogStream::Stderr, STDERR_TAIL_LINES),
+        restart_in_ms: backoff.map(|backoff| backoff.as_millis() as u64),
+        gave_up: should_restart && backoff.is_none(),
+    };
+    eprintln!(
+        "MCP server {} exited: {} (restart in {:?})",
+        name, record.status, backoff
+    );
+
+    save_crash(app, name, &record);
+    let _ = app.emit(
+        SERVER_CRASHED_EVENT,
+        ServerCrash {
+            server: name.to_string(),
+            record,
+        },
+    );
+}
+
+fn schedule_restart<R: Runtime>(app: AppHandle<R>, name: String, backoff: Duration) {
+    if let Ok(mut pending) = PENDING_RESTARTS.lock() {
+        pending.insert(name.clone());
+    }
+    let generation = stop_generation(&name);
+
+    thread::spawn(move || {
+        thread::sleep(backoff);
+
+        // 等待期间被手动停止或已重新启动时不再重启
+        let still_pending = PENDING_RESTARTS
+            .lock()
+            .map(|mut pending| pending.remove(&name))
+            .unwrap_or(false);
+        if !still_pending
+            || stop_generation(&name) != generation
+            || mcp_runner::get_server_status(&name)
+        {
+            return;
+        }
+
+        let started = Instant::now();
+        let result = mcp_runner::resolved_server_entry(&app, &name).and_then(|entry| match entry {
+            McpServerEntry::Stdio(stdio) => mcp_runner::start_stdio_server(&app, &name, &stdio),
+            // 远程 server 没有本地进程，不会出现在这里
+            McpServerEntry::Remote(_) => Ok(()),
+        });
+
+        match result {
+            // 握手期间被手动停止时，停止的请求没有找到进程，这里补上
+            Ok(()) if stop_generation(&name) != generation => {
+                if let Err(e) = mcp_runner::stop_mcp_server(&name) {
+                    eprintln!("Failed to stop MCP server {}: {}", name, e);
+                }
+            }
+            Ok(()) => {
+                println!("Restarted MCP server: {}", name);
+                let _ = crate::tray::update_tray_status(app.clone());
+            }
+            Err(e) => {
+                let exit_code = e
+                    .context
+                    .get("exit_code")
+                    .and_then(|code| code.as_i64())
+                    .map(|code| code as i32);
+                handle_failure(&app, &name, exit_code, e.message, false, started.elapsed());
+            }
+        }
+    });
+}
+
+// 在后台检查本地 server 是否退出
+pub fn start_supervisor<R: Runtime>(app: AppHandle<R>) {
+    thread::spawn(move || loop {
+        for ExitedServer {
+            name,
+            status,
+            uptime,
+        } in mcp_runner::take_exited_servers()
+        {
+            handle_failure(
+                &app,
+                &name,
+                status.code(),
+                status.to_string(),
+                status.success(),
+                uptime,
+            );
+        }
+        thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+#[tauri::command]
+pub fn get_restart_policy(app: AppHandle, name: String) -> RestartPolicy {
+    load_policy(&app, &name)
+}
+
+#[tauri::command]
+pub fn set_restart_policy(
+    app: AppHandle,
+    name: String,
+    policy: RestartPolicy,
+) -> Result<(), AppError> {
+    crate::store::update_installed_servers(&app, |servers| {
+        let server = servers
+            .get_mut(&name)
+            .ok_or_else(|| AppError::server_not_found(&name))?;
+        server.restart_policy = policy;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn get_server_crashes(app: AppHandle, name: String) -> Result<Vec<CrashRecord>, AppError> {
+    Ok(crate::store::load_installed_servers(&app)?
+        .get(&name)
+        .map(|server| server.crashes.clone())
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn clear_server_crashes(app: AppHandle, name: String) -> Result<(), AppError> {
+    crate::store::update_installed_servers(&app, |servers| {
+        if let Some(server) = servers.get_mut(&name) {
+            server.crashes.clear();
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubles_backoff_until_the_restart_limit() {
+        let policy = RestartPolicy {
+            mode: RestartMode::Always,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 250,
+            max_restarts: 3,
+            window_secs: 60,
+        };
+        let name = "backoff-test";
+        let backoffs: Vec<Option<u64>> = (0..4)
+            .map(|_| next_backoff(name, &policy).map(|backoff| backoff.as_millis() as u64))
+            .collect();
+        assert_eq!(backoffs, vec![Some(100), Some(200), Some(250), None]);
+    }
+
+    #[test]
+    fn stopping_changes_the_generation() {
+        let name = "generation-test";
+        let before = stop_generation(name);
+        cancel_restart(name);
+        assert_ne!(stop_generation(name), before);
+
+        let before = stop_generation(name);
+        cancel_all_restarts();
+        assert_ne!(stop_generation(name), before);
+    }
+}
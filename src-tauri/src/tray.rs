@@ -1,7 +1,8 @@
-use crate::{claude_config, mcp_runner};
-use std::time::Duration;
+use crate::i18n::{t, Message};
+use crate::mcp_clients::McpClient;
+use crate::{claude_config, mcp_runner, profiles, store};
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{Menu, MenuItem, Submenu},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager, Runtime,
 };
@@ -17,9 +18,9 @@ fn create_status_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>>
 
         for name in services {
             let status_icon = if mcp_runner::get_server_status(name) {
-                "运行中"
+                t(Message::TrayRunning)
             } else {
-                "已停止"
+                t(Message::TrayStopped)
             };
 
             let service_item = MenuItem::with_id(
@@ -33,26 +34,63 @@ fn create_status_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>>
         }
     }
 
+    let profiles_menu = create_profiles_menu(app)?;
+
     // 添加 Show App 菜单项
-    let show_app = MenuItem::with_id(app, "show_app", "Show App", true, None::<&str>)?;
-    menu_items.push(show_app);
+    let show_app = MenuItem::with_id(app, "show_app", t(Message::TrayShowApp), true, None::<&str>)?;
 
     // 添加 Quit 菜单项
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    menu_items.push(quit);
+    let quit = MenuItem::with_id(app, "quit", t(Message::TrayQuit), true, None::<&str>)?;
 
-    let menu_item_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> = menu_items
+    // 依次为: 服务状态、profile 子菜单、Show App、Quit
+    let mut menu_item_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> = menu_items
         .iter()
         .map(|item| item as &dyn tauri::menu::IsMenuItem<R>)
         .collect();
+    if let Some(profiles_menu) = &profiles_menu {
+        menu_item_refs.push(profiles_menu);
+    }
+    menu_item_refs.push(&show_app);
+    menu_item_refs.push(&quit);
     Menu::with_items(app, &menu_item_refs)
 }
 
+// 创建 profile 子菜单，没有 profile 时不显示
+fn create_profiles_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Option<Submenu<R>>> {
+    let profiles = store::load_profiles(app).unwrap_or_default();
+    if profiles.is_empty() {
+        return Ok(None);
+    }
+
+    let active = store::load_active_profile(app).ok().flatten();
+    let mut items = Vec::new();
+    for name in profiles.keys() {
+        let label = if active.as_deref() == Some(name.as_str()) {
+            format!("✓ {}", name)
+        } else {
+            name.clone()
+        };
+        items.push(MenuItem::with_id(
+            app,
+            format!("profile_{}", name),
+            label,
+            true,
+            None::<&str>,
+        )?);
+    }
+
+    let item_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> = items
+        .iter()
+        .map(|item| item as &dyn tauri::menu::IsMenuItem<R>)
+        .collect();
+    Submenu::with_items(app, t(Message::TrayProfiles), true, &item_refs).map(Some)
+}
+
 // 创建托盘
 pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
     let menu = create_status_menu(app)?;
 
-    let tray = TrayIconBuilder::with_id("tray")
+    TrayIconBuilder::with_id("tray")
         .menu(&menu)
         .tooltip("tauri")
         .icon(app.default_window_icon().unwrap().clone())
@@ -68,23 +106,24 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
                     eprintln!("Error stopping servers during shutdown: {}", e);
                 }
                 app.exit(0);
+            } else if let Some(name) = id.as_ref().strip_prefix("profile_") {
+                // 托盘中切换的 profile 作用于 Claude Desktop
+                if let Err(e) = profiles::apply_profile(app, name, McpClient::ClaudeDesktop) {
+                    eprintln!("Failed to switch profile '{}': {}", name, e);
+                }
+                let _ = update_tray_status(app.clone());
             }
         })
         .on_tray_icon_event(|tray, event| match event {
             TrayIconEvent::Click {
-                id: _,
                 position,
-                rect: _,
-                button,
-                button_state: _,
-            } => match button {
-                MouseButton::Right => {
-                    tray.app_handle()
-                        .emit("tray_contextmenu", position)
-                        .unwrap();
-                }
-                _ => {}
-            },
+                button: MouseButton::Right,
+                ..
+            } => {
+                tray.app_handle()
+                    .emit("tray_contextmenu", position)
+                    .unwrap();
+            }
             TrayIconEvent::Enter {
                 id: _,
                 position,
@@ -103,16 +142,6 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
         })
         .build(app)?;
 
-    // 添加定时刷新
-    let app_handle = app.clone();
-    let tray_handle = tray.clone();
-    std::thread::spawn(move || loop {
-        std::thread::sleep(Duration::from_secs(5));
-        if let Ok(new_menu) = create_status_menu(&app_handle) {
-            let _ = tray_handle.set_menu(Some(new_menu));
-        }
-    });
-
     Ok(())
 }
 
@@ -0,0 +1,359 @@
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Message;
+use crate::secrets::walk_strings;
+use crate::server_entry::McpServerEntry;
+use crate::store;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use serde_json::Value as JsonValue;
+use std::env;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::{AppHandle, Runtime};
+
+// 内置变量，不能被用户定义的变量覆盖
+const HOME_VARIABLE: &str = "HOME";
+const WORKSPACE_VARIABLE: &str = "workspace";
+const ENV_PREFIX: &str = "env:";
+// 交给 secrets 模块处理的引用
+const SECRET_PREFIX: &str = "secret:";
+// 由客户端自己展开的变量，例如 VS Code 的 ${workspaceFolder} 和 ${input:id}，原样保留
+const CLIENT_VARIABLES: [&str; 4] = [
+    "workspaceFolder",
+    "workspaceFolderBasename",
+    "userHome",
+    "pathSeparator",
+];
+const CLIENT_PREFIXES: [&str; 3] = ["input:", "config:", "command:"];
+
+// 工作区目录，启动时从 store 读取；VS Code 工作区配置的位置依赖它
+static WORKSPACE_DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn workspace_dir() -> Option<PathBuf> {
+    WORKSPACE_DIR.read().ok().and_then(|dir| dir.clone())
+}
+
+fn set_workspace_dir_setting(path: Option<&str>) {
+    if let Ok(mut dir) = WORKSPACE_DIR.write() {
+        *dir = path.map(PathBuf::from);
+    }
+}
+
+pub fn init_workspace_dir<R: Runtime>(app: &AppHandle<R>) {
+    match store::load_workspace_dir(app) {
+        Ok(path) => set_workspace_dir_setting(path.as_deref()),
+        Err(e) => eprintln!("Failed to load workspace directory: {}", e),
+    }
+}
+
+// 展开变量时用到的值
+struct VariableContext {
+    home: Option<String>,
+    workspace: Option<String>,
+    variables: IndexMap<String, String>,
+}
+
+impl VariableContext {
+    fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Self, AppError> {
+        let home = if cfg!(target_os = "windows") {
+            env::var("USERPROFILE").or_else(|_| env::var("HOME"))
+        } else {
+            env::var("HOME")
+        };
+
+        Ok(VariableContext {
+            home: home.ok(),
+            workspace: store::load_workspace_dir(app)?,
+            variables: store::load_variables(app)?,
+        })
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+            return env::var(var).ok();
+        }
+
+        match name {
+            HOME_VARIABLE => self.home.clone(),
+            WORKSPACE_VARIABLE => self.workspace.clone(),
+            _ => self.variables.get(name).cloned(),
+        }
+    }
+
+    // 不由本程序展开、需要原样保留的引用
+    fn is_passthrough(name: &str) -> bool {
+        name.starts_with(SECRET_PREFIX)
+            || CLIENT_VARIABLES.contains(&name)
+            || CLIENT_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+    }
+
+    // 展开 ${NAME}，$${ 表示字面量 ${；客户端变量和密钥引用原样保留，
+    // 其它无法解析的变量（包括拼错或已删除的变量）记入 unresolved
+    fn expand(&self, text: &str, unresolved: &mut Vec<String>) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find('$') {
+            output.push_str(&rest[..start]);
+            let after = &rest[start..];
+
+            if let Some(escaped) = after.strip_prefix("$${") {
+                output.push_str("${");
+                rest = escaped;
+                continue;
+            }
+
+            let Some(body) = after.strip_prefix("${") else {
+                output.push('$');
+                rest = &after[1..];
+                continue;
+            };
+            let Some(end) = body.find('}') else {
+                output.push_str(after);
+                rest = "";
+                break;
+            };
+
+            let name = &body[..end];
+            let reference = &after[..end + 3];
+            if Self::is_passthrough(name) {
+                output.push_str(reference);
+            } else if let Some(value) = self.lookup(name) {
+                output.push_str(&value);
+            } else {
+                if !unresolved.iter().any(|r| r == reference) {
+                    unresolved.push(reference.to_string());
+                }
+                output.push_str(reference);
+            }
+            rest = &body[end + 1..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+
+    fn expand_strings(&self, value: &mut JsonValue, unresolved: &mut Vec<String>) {
+        match value {
+            JsonValue::String(text) if text.contains('$') => {
+                *text = self.expand(text, unresolved);
+            }
+            JsonValue::Array(items) => {
+                for item in items {
+                    self.expand_strings(item, unresolved);
+                }
+            }
+            JsonValue::Object(map) => {
+                for (_, item) in map.iter_mut() {
+                    self.expand_strings(item, unresolved);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// 展开 server 配置中 args、env 和 cwd 里的变量，再替换密钥引用
+pub fn resolve_value<R: Runtime>(
+    app: &AppHandle<R>,
+    value: &mut JsonValue,
+) -> Result<(), AppError> {
+    let context = VariableContext::load(app)?;
+    let mut unresolved = Vec::new();
+
+    if let JsonValue::Object(server) = value {
+        for field in ["args", "env", "cwd"] {
+            if let Some(field_value) = server.get_mut(field) {
+                context.expand_strings(field_value, &mut unresolved);
+            }
+        }
+    }
+
+    if !unresolved.is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::VariableUnresolved,
+            Message::VariablesUnresolved,
+            &[("variables", &unresolved.join(", "))],
+        )
+        .with("variables", serde_json::json!(unresolved)));
+    }
+
+    crate::secrets::resolve_value(app, value)
+}
+
+// 生成客户端配置或启动进程前得到实际的配置
+pub fn resolve_entry<R: Runtime>(
+    app: &AppHandle<R>,
+    entry: &McpServerEntry,
+) -> Result<McpServerEntry, AppError> {
+    let mut value = serde_json::to_value(entry)
+        .map_err(|e| AppError::internal(format!("Failed to serialize server: {}", e)))?;
+    resolve_value(app, &mut value)?;
+    serde_json::from_value(value)
+        .map_err(|e| AppError::internal(format!("Failed to parse server: {}", e)))
+}
+
+// template 中含有变量或密钥引用的字段，如果 target 中对应的值就是展开后的结果，
+// 则改回引用，避免把本机路径和明文密钥写进 store
+pub fn restore_references<R: Runtime>(
+    app: &AppHandle<R>,
+    template: &JsonValue,
+    target: &mut JsonValue,
+) -> Result<(), AppError> {
+    // 变量或密钥已被删除时无法判断 target 中的值是否由引用展开而来，
+    // 返回错误，避免把展开后的值写进 store
+    let mut resolved = template.clone();
+    resolve_value(app, &mut resolved).map_err(|mut e| {
+        e.message = format!("Failed to restore variable references: {}", e.message);
+        e
+    })?;
+
+    let mut references = Vec::new();
+    walk_strings(template, String::new(), &mut |pointer, text| {
+        if text.contains("${") {
+            references.push((pointer.to_string(), text));
+        }
+    });
+
+    for (pointer, text) in references {
+        let (Some(current), Some(expanded)) =
+            (target.pointer_mut(&pointer), resolved.pointer(&pointer))
+        else {
+            continue;
+        };
+        if current == expanded {
+            *current = JsonValue::String(text.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn restore_entry_references<R: Runtime>(
+    app: &AppHandle<R>,
+    template: &McpServerEntry,
+    target: &mut McpServerEntry,
+) -> Result<(), AppError> {
+    let serialize_error =
+        |e: serde_json::Error| AppError::internal(format!("Failed to serialize server: {}", e));
+    let template = serde_json::to_value(template).map_err(serialize_error)?;
+    let mut value = serde_json::to_value(&*target).map_err(serialize_error)?;
+    restore_references(app, &template, &mut value)?;
+    *target = serde_json::from_value(value)
+        .map_err(|e| AppError::internal(format!("Failed to parse server: {}", e)))?;
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<(), AppError> {
+    if name == HOME_VARIABLE || name == WORKSPACE_VARIABLE {
+        return Err(AppError::localized(
+            ErrorCode::VariableNameInvalid,
+            Message::VariableBuiltIn,
+            &[("variable", name)],
+        )
+        .with("variable", name));
+    }
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+    {
+        return Err(AppError::localized(
+            ErrorCode::VariableNameInvalid,
+            Message::VariableNameInvalid,
+            &[("variable", name)],
+        )
+        .with("variable", name));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_variables(app: AppHandle) -> Result<IndexMap<String, String>, AppError> {
+    store::load_variables(&app)
+}
+
+#[tauri::command]
+pub fn set_variable(app: AppHandle, name: String, value: String) -> Result<(), AppError> {
+    validate_name(&name)?;
+
+    let mut variables = store::load_variables(&app)?;
+    variables.insert(name, value);
+    store::save_variables(&app, &variables)
+}
+
+#[tauri::command]
+pub fn delete_variable(app: AppHandle, name: String) -> Result<(), AppError> {
+    let mut variables = store::load_variables(&app)?;
+    if variables.shift_remove(&name).is_none() {
+        return Err(AppError::localized(
+            ErrorCode::VariableNotFound,
+            Message::VariableNotFound,
+            &[("variable", &name)],
+        )
+        .with("variable", name.as_str()));
+    }
+    store::save_variables(&app, &variables)
+}
+
+#[tauri::command]
+pub fn get_workspace_dir(app: AppHandle) -> Result<Option<String>, AppError> {
+    store::load_workspace_dir(&app)
+}
+
+#[tauri::command]
+pub fn set_workspace_dir(app: AppHandle, path: Option<String>) -> Result<(), AppError> {
+    store::save_workspace_dir(&app, path.as_deref())?;
+    set_workspace_dir_setting(path.as_deref());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> VariableContext {
+        VariableContext {
+            home: Some("/home/me".to_string()),
+            workspace: None,
+            variables: IndexMap::from([("root".to_string(), "/srv".to_string())]),
+        }
+    }
+
+    fn expand(text: &str) -> (String, Vec<String>) {
+        let mut unresolved = Vec::new();
+        let output = context().expand(text, &mut unresolved);
+        (output, unresolved)
+    }
+
+    #[test]
+    fn expands_known_variables() {
+        assert_eq!(
+            expand("${HOME}/data:${root}/x"),
+            ("/home/me/data:/srv/x".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn keeps_escaped_references() {
+        assert_eq!(
+            expand("$${HOME} $5"),
+            ("${HOME} $5".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn reports_unresolved_variables() {
+        let (output, unresolved) = expand("${workspace}/${HOEM}/${HOEM}");
+        assert_eq!(output, "${workspace}/${HOEM}/${HOEM}");
+        assert_eq!(unresolved, vec!["${workspace}", "${HOEM}"]);
+    }
+
+    #[test]
+    fn keeps_client_and_secret_references() {
+        let text = "${workspaceFolder}/${input:token}/${secret:api}";
+        assert_eq!(expand(text), (text.to_string(), Vec::new()));
+    }
+}
@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { BrowserRouter, Routes, Route } from "react-router-dom";
 import { Sidebar } from "./components/Sidebar";
 import { StatusPage } from "./pages/Status";
@@ -186,6 +187,15 @@ function App() {
     updateServerStatus();
   }, [updateServerStatus, claudeConfig]);
 
+  useEffect(() => {
+    const unlisten = listen("claude-config-changed", () => {
+      checkClaudeConfig().then(setClaudeConfig);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [checkClaudeConfig]);
+
   useEffect(() => {
     console.log('Config changed:', claudeConfig);
     if (claudeConfig) {
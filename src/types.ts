@@ -2,11 +2,19 @@ export interface McpServerConfig {
   command: string;
   args: string[];
   env?: Record<string, string> | null;
+  cwd?: string;
+  // remote servers (sse / streamable http)
+  type?: "sse" | "http";
+  url?: string;
+  headers?: Record<string, string> | null;
 }
 
 export interface ClaudeConfig {
   mcpServers: Record<string, McpServerConfig>;
-  globalShortcut: string;
+  // absent until a shortcut has been set
+  globalShortcut?: string;
+  // content hash of the file when it was read, used to detect external edits
+  __revision?: string;
 }
 
 export interface McpServerArgs {
@@ -66,4 +74,235 @@ export interface InstalledMcpServer {
   env: Record<string, string> | null;
   require_file_path: boolean;
   repo_url: string;
-} 
\ No newline at end of file
+  restart_policy?: RestartPolicy;
+  crashes?: CrashRecord[];
+}
+
+export interface ConfigChange {
+  kind: "added" | "removed" | "modified";
+  // JSON Pointer of the changed field
+  path: string;
+  before?: unknown;
+  after?: unknown;
+}
+
+// returned by mutating commands; with dry_run the file is left untouched
+export interface ConfigPreview {
+  client: string;
+  path: string;
+  before: Record<string, unknown>;
+  after: Record<string, unknown>;
+  changes: ConfigChange[];
+}
+
+// error returned by backend commands; `code` is stable and safe to switch on
+export interface AppError {
+  code:
+    | "config_parse"
+    | "config_conflict"
+    | "config_invalid"
+    | "config_unsupported"
+    | "config_path_invalid"
+    | "server_not_found"
+    | "server_already_installed"
+    | "server_already_disabled"
+    | "server_not_disabled"
+    | "server_is_remote"
+    | "server_start_failed"
+    | "server_not_running"
+    | "remote_connect_failed"
+    | "rpc_error"
+    | "request_timeout"
+    | "invalid_arguments"
+    | "backup_not_found"
+    | "backup_id_invalid"
+    | "profile_not_found"
+    | "profile_invalid"
+    | "secret_not_found"
+    | "secret_already_exists"
+    | "secret_name_invalid"
+    | "secret_in_use"
+    | "secret_corrupted"
+    | "vault_key_invalid"
+    | "variable_not_found"
+    | "variable_name_invalid"
+    | "variable_unresolved"
+    | "process_failed"
+    | "env_install_failed"
+    | "unsupported_environment"
+    | "unsupported_platform"
+    | "bundle_invalid"
+    | "home_not_found"
+    | "io"
+    | "store"
+    | "network"
+    | "api"
+    | "internal";
+  message: string;
+  // related path, server name, line/column, ...
+  context: Record<string, unknown>;
+}
+
+// language of backend messages (tray, env check, errors)
+export type Language = "en" | "zh" | "ja";
+
+export interface LanguageSettings {
+  language: Language;
+  // null follows the system locale
+  preference: Language | null;
+}
+
+// where the Claude Desktop config path came from, highest priority first
+export type ConfigPathSource = "env" | "setting" | "xdg_config_home" | "default";
+
+export interface ResolvedConfigPath {
+  path: string;
+  source: ConfigPathSource;
+}
+
+// which variables a started server inherits from MCP Manager's environment
+export type EnvInheritPolicy = "minimal" | "all" | "none";
+
+// exactly how a stdio server is launched
+export interface LaunchSpec {
+  name: string;
+  command: string;
+  resolved_command: string | null;
+  args: string[];
+  cwd: string | null;
+  env: Record<string, string>;
+  inherit_env: EnvInheritPolicy;
+}
+
+export type LogStream = "stdout" | "stderr";
+
+// one line of a server's stdout / stderr
+export interface LogLine {
+  seq: number;
+  server: string;
+  stream: LogStream;
+  timestamp: string;
+  line: string;
+}
+
+// a server's log file on disk; `current` is the file still being written
+export interface LogFileInfo {
+  file_name: string;
+  size: number;
+  modified: string;
+  current: boolean;
+}
+
+// result of the MCP initialize handshake with a local server
+export interface ServerHandshake {
+  protocol_version: string;
+  server_info: { name: string; version: string; [key: string]: unknown };
+  capabilities: Record<string, unknown>;
+  instructions: string | null;
+  initialized_at: string;
+}
+
+export interface ServerDetails {
+  running: boolean;
+  handshake: ServerHandshake | null;
+}
+
+// an entry of a running server's tools/list
+export interface McpTool {
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  inputSchema: Record<string, unknown>;
+  outputSchema?: Record<string, unknown> | null;
+  annotations?: Record<string, unknown> | null;
+}
+
+export interface ToolCallResult {
+  content: Record<string, unknown>[];
+  structured_content: unknown | null;
+  is_error: boolean;
+  duration_ms: number;
+}
+
+export interface McpResource {
+  uri: string;
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  mimeType?: string | null;
+  size?: number | null;
+  annotations?: Record<string, unknown> | null;
+}
+
+export interface McpResourceTemplate {
+  uriTemplate: string;
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  mimeType?: string | null;
+  annotations?: Record<string, unknown> | null;
+}
+
+// resources/read content; binary blobs stay base64 encoded
+export type ResourceContent =
+  | { kind: "text"; uri: string; mime_type: string | null; text: string }
+  | {
+      kind: "binary";
+      uri: string;
+      mime_type: string | null;
+      blob: string;
+      size: number | null;
+    };
+
+export interface McpPromptArgument {
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  required: boolean;
+}
+
+export interface McpPrompt {
+  name: string;
+  title?: string | null;
+  description?: string | null;
+  arguments: McpPromptArgument[];
+}
+
+export interface PromptResult {
+  description: string | null;
+  messages: { role: "user" | "assistant"; content: Record<string, unknown> }[];
+}
+
+export type RestartMode = "never" | "on_failure" | "always";
+
+export interface RestartPolicy {
+  mode: RestartMode;
+  // delay before the first restart, doubled for each further restart
+  initial_backoff_ms: number;
+  max_backoff_ms: number;
+  // at most max_restarts restarts within window_secs
+  max_restarts: number;
+  window_secs: number;
+}
+
+export interface CrashRecord {
+  at: string;
+  exit_code: number | null;
+  status: string;
+  uptime_ms: number;
+  stderr_tail: string[];
+  restart_in_ms: number | null;
+  gave_up: boolean;
+}
+
+// payload of the "mcp-server-crashed" event
+export interface ServerCrash {
+  server: string;
+  record: CrashRecord;
+}
+
+// payload of "claude-config-watcher-status" and get_config_watcher_status
+export interface ConfigWatcherStatus {
+  watching: boolean;
+  error: AppError | null;
+}